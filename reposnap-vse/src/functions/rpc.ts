@@ -189,9 +189,35 @@ export function wireRpc(
 
         case "getApplicationSettings": {
           const defaultSettings: ApplicationSettings = {
-            schemaVersion: 1,
+            schemaVersion: 2,
             appVersion: "treesnap",
             treeOption: "include",
+            // The extension has its own ignore list
+            ignorePatterns: [],
+            treeLoadDepth: null,
+            treeStyle: "ascii",
+            exportFormat: "classic",
+            relativePaths: true,
+            fileMap: { annotate: false, collapseChains: false },
+            transforms: {
+              stripComments: false,
+              collapseBlankLines: false,
+              trimTrailingWhitespace: false,
+            },
+            lineNumbers: false,
+            diff: { against: "head", contextLines: 3 },
+            redaction: { enabled: true, customRules: [] },
+            tokenBudget: null,
+            partTokenLimit: 32000,
+            languageOverrides: {},
+            fileSizeCap: {
+              enabled: true,
+              maxBytes: 1048576,
+              maxTokens: 50000,
+              headLines: 200,
+              tailLines: 50,
+            },
+            metadataHeaderFormats: [],
           };
           result = { settings: defaultSettings } as { settings: ApplicationSettings };
           break;
@@ -6,6 +6,9 @@ export interface FileTreeNode {
   selected?: boolean;
   tokenCount?: number;
   token_count?: number;
+  size?: number | null;
+  language?: string | null;
+  encoding?: string | null;
   parent?: string;
   dirPercentage?: number;
   isLoading?: boolean;
@@ -22,6 +25,9 @@ export interface RecentWorkspace {
 }
 
 export type TreeOption = "include" | "include-only-selected" | "do-not-include";
+export type TreeStyle = "ascii" | "path-list" | "markdown-list" | "json";
+
+export type ExportFormat = "classic" | "xml" | "markdown" | "json" | "plain-text";
 
 export interface LocalLicenseState {
   status: "inactive" | "activated" | "expired";
@@ -36,10 +42,72 @@ export interface ApiError {
   message: string;
 }
 
+export interface ContentTransforms {
+  stripComments: boolean;
+  collapseBlankLines: boolean;
+  trimTrailingWhitespace: boolean;
+}
+
+export type DiffOptions = (
+  | { against: "head" }
+  | { against: "ref"; ref: string }
+  | { against: "staged" }
+) & {
+  contextLines?: number;
+};
+
+export interface RedactionRule {
+  name: string;
+  pattern: string;
+}
+
+export interface RedactionSettings {
+  enabled: boolean;
+  customRules: RedactionRule[];
+}
+
+export interface TokenBudget {
+  maxTokens: number;
+  priority?: "smallest-first" | "selection-order" | "most-recently-modified";
+  truncateOverflow?: boolean;
+}
+
+export interface FileSizeCap {
+  enabled: boolean;
+  maxBytes: number;
+  maxTokens: number;
+  headLines: number;
+  tailLines: number;
+}
+
+export interface FileMapOptions {
+  annotate: boolean;
+  maxDepth?: number | null;
+  collapseChains: boolean;
+  elideOver?: number | null;
+}
+
 export interface ApplicationSettings {
   schemaVersion: number;
   appVersion: string;
   treeOption: TreeOption;
+  ignorePatterns: string[];
+  treeLoadDepth: number | null;
+  treeStyle: TreeStyle;
+  exportFormat: ExportFormat;
+  relativePaths: boolean;
+  rootDisplayName?: string | null;
+  fileMap: FileMapOptions;
+  transforms: ContentTransforms;
+  lineNumbers: boolean;
+  diff: DiffOptions;
+  promptTemplate?: string | null;
+  redaction: RedactionSettings;
+  tokenBudget: TokenBudget | null;
+  partTokenLimit: number;
+  languageOverrides: Record<string, string>;
+  fileSizeCap: FileSizeCap;
+  metadataHeaderFormats: ExportFormat[];
 }
 
 export type TauriApiErrorInternal = {
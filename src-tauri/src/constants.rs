@@ -1,4 +1,5 @@
-// Hard-coded default ignore patterns (akin to your Node code's default-ignore.ts).
+// Ignore patterns new installs start with and "reset to defaults" goes back to;
+// the ones in use are ApplicationSettings::ignore_patterns.
 pub const DEFAULT_IGNORE_PATTERNS: &str = r#"
 codefetch/
 .git/
@@ -1,4 +1,16 @@
+use crate::constants::DEFAULT_IGNORE_PATTERNS;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{
+    content_transforms::ContentTransforms, diff_options::DiffOptions, export_format::ExportFormat,
+    file_map_options::FileMapOptions, file_size_cap::FileSizeCap, redaction::RedactionSettings,
+    token_budget::TokenBudget, tree_style::TreeStyle,
+};
+
+/// Version of the settings layout; saved settings with an older one are
+/// migrated when they're loaded.
+pub const SCHEMA_VERSION: u32 = 2;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -6,14 +18,104 @@ pub struct ApplicationSettings {
     pub schema_version: u32,
     pub app_version: String,
     pub tree_option: String,
+    /// Gitignore-style patterns left out of every workspace, on top of what
+    /// git ignores. `.treesnapignore` files can bring them back.
+    #[serde(default = "default_ignore_patterns")]
+    pub ignore_patterns: Vec<String>,
+    /// Levels of the tree loaded when a workspace is opened, deeper directories
+    /// are loaded as they're expanded; `None` loads everything up front.
+    #[serde(default)]
+    pub tree_load_depth: Option<usize>,
+    /// How the file map lists the files `tree_option` puts in it.
+    #[serde(default)]
+    pub tree_style: TreeStyle,
+    #[serde(default)]
+    pub export_format: ExportFormat,
+    /// Write paths relative to the workspace root instead of absolute.
+    #[serde(default = "default_relative_paths")]
+    pub relative_paths: bool,
+    /// Name shown for the workspace root when paths are relative; defaults to
+    /// the root directory's name.
+    #[serde(default)]
+    pub root_display_name: Option<String>,
+    /// How the file map is drawn.
+    #[serde(default)]
+    pub file_map: FileMapOptions,
+    #[serde(default)]
+    pub transforms: ContentTransforms,
+    /// Prefix every exported line with its number in the file, so a model can
+    /// cite lines.
+    #[serde(default)]
+    pub line_numbers: bool,
+    /// What diff exports compare against, and how much context they show.
+    #[serde(default)]
+    pub diff: DiffOptions,
+    /// Prompt template every export is wrapped in, by name; `None` for the
+    /// plain export.
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    /// Secret detection applied to everything that gets exported.
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    /// Budget every export is fitted into; `None` exports every selected file.
+    #[serde(default)]
+    pub token_budget: Option<TokenBudget>,
+    /// Most tokens in each part when an export is split into parts.
+    #[serde(default = "default_part_token_limit")]
+    pub part_token_limit: usize,
+    /// Code fence languages that replace the detected ones, keyed by exact
+    /// file name (`Jenkinsfile`) or extension pattern (`*.h`).
+    #[serde(default)]
+    pub language_overrides: HashMap<String, String>,
+    #[serde(default)]
+    pub file_size_cap: FileSizeCap,
+    /// Formats whose exports start with a header recording the workspace,
+    /// git revision, file and token counts.
+    #[serde(default)]
+    pub metadata_header_formats: Vec<ExportFormat>,
+}
+
+/// The ignore patterns new installs start with.
+pub fn default_ignore_patterns() -> Vec<String> {
+    DEFAULT_IGNORE_PATTERNS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_relative_paths() -> bool {
+    true
+}
+
+fn default_part_token_limit() -> usize {
+    32_000
 }
 
 impl Default for ApplicationSettings {
     fn default() -> Self {
         Self {
-            schema_version: 1,
+            schema_version: SCHEMA_VERSION,
             app_version: env!("CARGO_PKG_VERSION").to_string(),
             tree_option: "include".to_string(),
+            ignore_patterns: default_ignore_patterns(),
+            tree_load_depth: None,
+            tree_style: TreeStyle::default(),
+            export_format: ExportFormat::default(),
+            relative_paths: default_relative_paths(),
+            root_display_name: None,
+            file_map: FileMapOptions::default(),
+            transforms: ContentTransforms::default(),
+            line_numbers: false,
+            diff: DiffOptions::default(),
+            prompt_template: None,
+            redaction: RedactionSettings::default(),
+            token_budget: None,
+            part_token_limit: default_part_token_limit(),
+            language_overrides: HashMap::new(),
+            file_size_cap: FileSizeCap::default(),
+            metadata_header_formats: Vec::new(),
         }
     }
 }
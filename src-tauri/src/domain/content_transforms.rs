@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Optional clean-ups applied to file content between reading and exporting it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentTransforms {
+    /// Remove comments, for languages whose comment syntax is known.
+    pub strip_comments: bool,
+    /// Collapse runs of blank lines into a single blank line.
+    pub collapse_blank_lines: bool,
+    pub trim_trailing_whitespace: bool,
+}
+
+impl ContentTransforms {
+    pub fn is_empty(&self) -> bool {
+        !self.strip_comments && !self.collapse_blank_lines && !self.trim_trailing_whitespace
+    }
+}
+
+/// Token counts of a selection with and without the content transforms.
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformSavings {
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// What the working tree is compared against in a diff export.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "against")]
+pub enum DiffBase {
+    /// Working tree and index against the current commit.
+    #[default]
+    #[serde(rename = "head")]
+    Head,
+    /// Working tree and index against any revision git understands, e.g. a
+    /// branch, tag or commit SHA.
+    #[serde(rename = "ref")]
+    Ref {
+        #[serde(rename = "ref")]
+        name: String,
+    },
+    /// Staged changes only: the index against the current commit.
+    #[serde(rename = "staged")]
+    Staged,
+}
+
+/// Exports the unified diff of each selected file instead of its content.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffOptions {
+    #[serde(flatten)]
+    pub base: DiffBase,
+    /// Unchanged lines shown around each hunk.
+    #[serde(default = "default_context_lines")]
+    pub context_lines: u32,
+}
+
+fn default_context_lines() -> u32 {
+    3
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            base: DiffBase::default(),
+            context_lines: default_context_lines(),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Layout used when rendering selected files into an export.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// `<file_contents>` block with "File: path" headers and fenced content.
+    #[default]
+    #[serde(rename = "classic")]
+    Classic,
+    /// Each file wrapped in a `<file path="...">` tag.
+    #[serde(rename = "xml")]
+    Xml,
+    /// Markdown headings and fenced code blocks only.
+    #[serde(rename = "markdown")]
+    Markdown,
+    /// A JSON document with a `files` array of `{path, language, content}`.
+    #[serde(rename = "json")]
+    Json,
+    /// Plain text with ruled separators between files.
+    #[serde(rename = "plain-text")]
+    PlainText,
+}
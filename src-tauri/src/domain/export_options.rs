@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+use super::{
+    content_transforms::ContentTransforms, diff_options::DiffOptions, export_format::ExportFormat,
+    token_budget::TokenBudget,
+};
+
+/// Per-export choices sent along with the selected files.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    /// Overrides the format saved in `ApplicationSettings` for this export.
+    pub format: Option<ExportFormat>,
+    pub token_budget: Option<TokenBudget>,
+    /// Overrides the transforms saved in `ApplicationSettings` for this export.
+    pub transforms: Option<ContentTransforms>,
+    /// Overrides the line numbering saved in `ApplicationSettings` for this export.
+    pub line_numbers: Option<bool>,
+    /// Export each file's diff instead of its content.
+    pub diff: Option<DiffOptions>,
+    /// Name of the prompt template to wrap the export in. Overrides the
+    /// template chosen in `ApplicationSettings`.
+    pub template: Option<String>,
+    /// Fills the template's `{{instructions}}` placeholder.
+    pub instructions: Option<String>,
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+use super::redaction::RedactedSecret;
+
+/// One part of an export split to fit a token limit.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPart {
+    /// 1-based position in the sequence.
+    pub number: usize,
+    pub total: usize,
+    /// The part's text, starting with a header that explains the sequence.
+    pub content: String,
+    pub token_count: usize,
+}
+
+/// An export split into parts, ready to be copied one at a time.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportParts {
+    pub parts: Vec<ExportPart>,
+    pub redactions: Vec<RedactedSecret>,
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+use super::{redaction::RedactedSecret, token_budget::OmittedFile};
+
+/// Result of writing an export to disk.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub path: String,
+    pub file_count: usize,
+    pub bytes_written: u64,
+    pub token_count: usize,
+    pub omitted_files: Vec<OmittedFile>,
+    pub redactions: Vec<RedactedSecret>,
+}
+
+/// Result of copying an export to the clipboard: what was kept out of it.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardSummary {
+    pub omitted_files: Vec<OmittedFile>,
+    pub redactions: Vec<RedactedSecret>,
+}
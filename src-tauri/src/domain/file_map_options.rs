@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// How the file map at the top of an export is drawn.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileMapOptions {
+    /// Show each file's token count and size, and each directory's token
+    /// total, as in `services/ (12.4k tok)`.
+    pub annotate: bool,
+    /// Directories this deep are shown with their file count instead of their
+    /// contents, unless they hold selected files. `None` for no limit.
+    pub max_depth: Option<usize>,
+    /// Show directories that only hold a single directory as one entry, `a/b/c/`.
+    pub collapse_chains: bool,
+    /// Directories without selected files and with more files than this are
+    /// shown as `dir/ (143 files)`. `None` to always show their contents.
+    pub elide_over: Option<usize>,
+}
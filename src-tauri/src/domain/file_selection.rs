@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// An inclusive, 1-based range of lines.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// How much of a file goes into the export.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentMode {
+    #[default]
+    #[serde(rename = "full")]
+    Full,
+    /// Type definitions, signatures and doc comments, with function bodies
+    /// replaced by `{ ... }`. Falls back to full content for languages
+    /// without a grammar, and is ignored for selections with line ranges,
+    /// which are exported as chosen.
+    #[serde(rename = "outline")]
+    Outline,
+}
+
+/// A file picked for export: either a plain path for the whole file, or a
+/// path with the line ranges to keep and how to show the file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum FileSelection {
+    Path(String),
+    Detailed {
+        path: String,
+        #[serde(default)]
+        ranges: Vec<LineRange>,
+        #[serde(default)]
+        mode: ContentMode,
+    },
+}
+
+impl FileSelection {
+    pub fn path(&self) -> &str {
+        match self {
+            FileSelection::Path(path) => path,
+            FileSelection::Detailed { path, .. } => path,
+        }
+    }
+
+    /// The chosen line ranges, empty when the whole file is selected.
+    pub fn line_ranges(&self) -> &[LineRange] {
+        match self {
+            FileSelection::Path(_) => &[],
+            FileSelection::Detailed { ranges, .. } => ranges,
+        }
+    }
+
+    pub fn mode(&self) -> ContentMode {
+        match self {
+            FileSelection::Path(_) => ContentMode::Full,
+            FileSelection::Detailed { mode, .. } => *mode,
+        }
+    }
+
+    /// Whether the file is exported exactly as it is on disk, which lets its
+    /// token count come straight from the token cache.
+    pub fn is_whole_file(&self) -> bool {
+        self.line_ranges().is_empty() && self.mode() == ContentMode::Full
+    }
+}
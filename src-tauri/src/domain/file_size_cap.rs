@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// Limits on how much of any one file goes into an export. A file over either
+/// limit is cut down to its first and last lines. Off unless turned on in
+/// settings, so existing workspaces keep exporting whole files.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileSizeCap {
+    pub enabled: bool,
+    /// Files larger than this on disk are never read in full; token counts
+    /// for them are estimated from their size.
+    pub max_bytes: u64,
+    pub max_tokens: usize,
+    /// Lines kept from the start of a capped file.
+    pub head_lines: usize,
+    /// Lines kept from the end of a capped file.
+    pub tail_lines: usize,
+}
+
+impl Default for FileSizeCap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: 1024 * 1024,
+            max_tokens: 50_000,
+            head_lines: 200,
+            tail_lines: 50,
+        }
+    }
+}
+
+impl FileSizeCap {
+    /// Whether a file of `size` bytes is too big to read in full.
+    pub fn exceeds_bytes(&self, size: u64) -> bool {
+        self.enabled && size > self.max_bytes
+    }
+}
@@ -6,6 +6,18 @@ pub struct FileTreeNode {
     pub path: String,
     pub children: Option<Vec<FileTreeNode>>,
     pub is_directory: bool,
+    /// Whether a directory has anything in it. In a lazily loaded tree a
+    /// directory with `children` still `None` hasn't been expanded yet.
+    pub has_children: bool,
+    /// A file's own count, or a directory's total over its files.
     pub token_count: Option<usize>,
     pub last_modified: Option<u64>,
+    /// Size of a file in bytes, `None` for directories.
+    pub size: Option<u64>,
+    /// Language of a file as used in code fences, `None` for directories and
+    /// files nothing is known about.
+    pub language: Option<String>,
+    /// Character encoding a text file was written in, such as `UTF-8` or
+    /// `Shift_JIS`; `None` for directories and binary files.
+    pub encoding: Option<String>,
 }
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+/// An ignore file with lines that couldn't be parsed. Its other lines still
+/// apply.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreFileError {
+    pub path: String,
+    pub message: String,
+}
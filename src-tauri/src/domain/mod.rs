@@ -1,2 +1,16 @@
 pub mod application_settings;
+pub mod content_transforms;
+pub mod diff_options;
+pub mod export_format;
+pub mod export_options;
+pub mod export_parts;
+pub mod export_summary;
+pub mod file_map_options;
+pub mod file_selection;
+pub mod file_size_cap;
 pub mod file_tree_node;
+pub mod ignore_file_error;
+pub mod prompt_template;
+pub mod redaction;
+pub mod token_budget;
+pub mod tree_style;
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// A named prompt that wraps an export, e.g. "Code review". The body holds
+/// `{{placeholder}}`s that are filled in when the export is rendered.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    pub name: String,
+    pub body: String,
+}
+
+impl PromptTemplate {
+    fn new(name: &str, body: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Templates offered before the user has saved any of their own.
+pub fn default_prompt_templates() -> Vec<PromptTemplate> {
+    vec![
+        PromptTemplate::new(
+            "Code review",
+            "Review the following code from {{workspace_name}} for bugs, unclear \
+             naming and missing error handling.\n\n{{instructions}}\n\n{{file_map}}\n\
+             {{file_contents}}",
+        ),
+        PromptTemplate::new(
+            "Write tests",
+            "Write unit tests for the following code from {{workspace_name}}, \
+             following the conventions already used in the project.\n\n\
+             {{instructions}}\n\n{{file_map}}\n{{file_contents}}",
+        ),
+        PromptTemplate::new(
+            "Explain architecture",
+            "Explain the architecture of {{workspace_name}}: its main modules, how \
+             they depend on each other and how data flows between them. The \
+             selection below is about {{token_count}} tokens.\n\n{{instructions}}\n\n\
+             {{file_map}}\n{{file_contents}}",
+        ),
+    ]
+}
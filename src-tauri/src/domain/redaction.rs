@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// A user-defined pattern whose matches are redacted as `[REDACTED:<name>]`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub name: String,
+    /// A regular expression. When it has a capture group only the group is
+    /// redacted, so `token=(\w+)` keeps the `token=` part.
+    pub pattern: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedactionSettings {
+    /// Run the built-in detectors and custom rules over every exported file.
+    pub enabled: bool,
+    pub custom_rules: Vec<RedactionRule>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            custom_rules: Vec::new(),
+        }
+    }
+}
+
+/// One value that was replaced in an export.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactedSecret {
+    /// The file's path as written into the export.
+    pub path: String,
+    /// Detector or custom rule that matched, as in `[REDACTED:<kind>]`.
+    pub kind: String,
+    /// 1-based line in the file on disk.
+    pub line: usize,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Order in which files claim room in a token budget; files late in the order
+/// are the ones dropped or truncated once the budget runs out.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BudgetPriority {
+    #[default]
+    #[serde(rename = "smallest-first")]
+    SmallestFirst,
+    #[serde(rename = "selection-order")]
+    SelectionOrder,
+    #[serde(rename = "most-recently-modified")]
+    MostRecentlyModified,
+}
+
+/// Upper bound on the file content tokens of a single export.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBudget {
+    pub max_tokens: usize,
+    #[serde(default)]
+    pub priority: BudgetPriority,
+    /// Truncate the first file that no longer fits instead of dropping it whole.
+    #[serde(default)]
+    pub truncate_overflow: bool,
+}
+
+/// A selected file left out of an export because it did not fit the budget.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OmittedFile {
+    pub path: String,
+    pub token_count: usize,
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// How the file map lists the workspace's files.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeStyle {
+    /// Box-drawing tree with `├──` and `└──` connectors.
+    #[default]
+    #[serde(rename = "ascii")]
+    Ascii,
+    /// One full path per line, the cheapest in tokens.
+    #[serde(rename = "path-list")]
+    PathList,
+    /// Nested Markdown bullet list.
+    #[serde(rename = "markdown-list")]
+    MarkdownList,
+    /// JSON tree of the serialized `FileTreeNode`s.
+    #[serde(rename = "json")]
+    Json,
+}
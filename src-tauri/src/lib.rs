@@ -9,13 +9,16 @@ mod services;
 
 use domain::file_tree_node::FileTreeNode;
 use services::cache_service;
+use services::export;
 use services::file_service;
+use services::ignore_service;
 use services::license;
 use services::settings_service;
 use services::token_service;
 use services::tree_service;
 use services::watcher_service;
 use std::collections::HashMap;
+use std::path::Path;
 
 // Conditional imports for debug commands
 #[cfg(debug_assertions)]
@@ -24,7 +27,15 @@ use services::license::debug as license_debug;
 use tauri_plugin_dialog;
 use tauri_plugin_fs;
 
-use domain::application_settings::ApplicationSettings;
+use domain::application_settings::{self, ApplicationSettings};
+use domain::content_transforms::{ContentTransforms, TransformSavings};
+use domain::export_options::ExportOptions;
+use domain::export_parts::ExportParts;
+use domain::export_summary::{ClipboardSummary, ExportSummary};
+use domain::file_selection::FileSelection;
+use domain::ignore_file_error::IgnoreFileError;
+use domain::prompt_template::PromptTemplate;
+use domain::redaction::{RedactionRule, RedactionSettings};
 use reqwest::Client;
 use services::license::errors::ApiError;
 use std::sync::Mutex;
@@ -34,42 +45,142 @@ use tracing_subscriber;
 
 #[tauri::command]
 async fn copy_files_with_tree_to_clipboard(
+    app_handle: AppHandle,
     dir_path: String,
-    selected_file_paths: Vec<String>,
+    selected_file_paths: Vec<FileSelection>,
     tree_option: String,
-) -> Result<(), String> {
-    // First get the content using our existing function
-    let content = match tree_option.as_str() {
-        "include" => {
-            // Get full tree
-            let tree = tree_service::get_file_tree(dir_path.clone(), false).await?;
-            let tree_text = file_service::generate_file_tree_text(&dir_path, &tree);
-            format!("<file_map>\n{}</file_map>\n\n", tree_text)
-        }
-        "include-only-selected" => {
-            // Get tree with only selected files
-            let mut tree = get_file_tree(dir_path.clone(), false).await?;
-            tree_service::filter_tree_to_selected(&mut tree, &selected_file_paths);
-            let tree_text = file_service::generate_file_tree_text(&dir_path, &tree);
-            format!("<file_map>\n{}</file_map>\n\n", tree_text)
-        }
-        "do-not-include" => String::new(),
-        _ => return Err("Invalid tree option".to_string()),
-    };
+    options: Option<ExportOptions>,
+) -> Result<ClipboardSummary, String> {
+    // Build the file map and fit the selected files into the token budget, if one was given
+    let plan = export::prepare_export(
+        &app_handle,
+        &dir_path,
+        &selected_file_paths,
+        &tree_option,
+        &options.unwrap_or_default(),
+    )
+    .await?;
+
+    let bpe = token_service::shared_bpe()?;
+
+    // Render the file map and file contents in the chosen format
+    let (output, redactions) = file_service::build_file_content_string(&plan, &bpe);
+
+    // Copy to clipboard, and report what was kept out of it
+    file_service::copy_to_clipboard(&output)?;
+    Ok(ClipboardSummary {
+        omitted_files: plan.omitted,
+        redactions,
+    })
+}
 
-    // Add file contents using helper
-    let mut output = content;
-    output.push_str(&file_service::build_file_content_string(
+/// Streams the export straight to `output_path` instead of the clipboard.
+#[tauri::command]
+async fn export_to_file(
+    app_handle: AppHandle,
+    dir_path: String,
+    selected_file_paths: Vec<FileSelection>,
+    tree_option: String,
+    output_path: String,
+    options: Option<ExportOptions>,
+) -> Result<ExportSummary, String> {
+    let plan = export::prepare_export(
+        &app_handle,
+        &dir_path,
         &selected_file_paths,
-    ));
+        &tree_option,
+        &options.unwrap_or_default(),
+    )
+    .await?;
+
+    let bpe = token_service::shared_bpe()?;
 
-    // Copy to clipboard
-    file_service::copy_to_clipboard(&output)
+    export::export_to_file(Path::new(&output_path), &plan, &bpe)
 }
 
+/// Splits the export into numbered parts of at most `max_tokens` tokens each, so
+/// they can be copied one at a time. Without a limit the one in settings is used.
 #[tauri::command]
-async fn get_file_tree(dir_path: String, with_tokens: bool) -> Result<Vec<FileTreeNode>, String> {
-    return tree_service::get_file_tree(dir_path, with_tokens).await;
+async fn split_export_into_parts(
+    app_handle: AppHandle,
+    dir_path: String,
+    selected_file_paths: Vec<FileSelection>,
+    tree_option: String,
+    max_tokens: Option<usize>,
+    options: Option<ExportOptions>,
+) -> Result<ExportParts, String> {
+    let plan = export::prepare_export(
+        &app_handle,
+        &dir_path,
+        &selected_file_paths,
+        &tree_option,
+        &options.unwrap_or_default(),
+    )
+    .await?;
+    let max_tokens = max_tokens.unwrap_or_else(|| {
+        settings_service::load_application_settings_internal(&app_handle).part_token_limit
+    });
+
+    let bpe = token_service::shared_bpe()?;
+    export::split_export(&plan, max_tokens, &bpe)
+}
+
+/// Copies text the frontend already has, such as one part of a split export.
+#[tauri::command]
+async fn copy_text_to_clipboard(text: String) -> Result<(), String> {
+    file_service::copy_to_clipboard(&text)
+}
+
+#[tauri::command]
+async fn get_file_tree(
+    app_handle: AppHandle,
+    dir_path: String,
+    with_tokens: bool,
+) -> Result<Vec<FileTreeNode>, String> {
+    let settings = settings_service::load_application_settings_internal(&app_handle);
+    return tree_service::get_file_tree(
+        dir_path,
+        with_tokens,
+        &settings.ignore_patterns,
+        &settings.language_overrides,
+        settings.file_size_cap,
+        None,
+    )
+    .await;
+}
+
+/// Loads a directory of a lazily loaded tree when it's expanded, `depth` levels
+/// deep or all the way down for `None`.
+#[tauri::command]
+async fn get_directory_children(
+    app_handle: AppHandle,
+    workspace_path: String,
+    path: String,
+    depth: Option<usize>,
+) -> Result<Vec<FileTreeNode>, String> {
+    let settings = settings_service::load_application_settings_internal(&app_handle);
+    tree_service::get_directory_children(
+        workspace_path,
+        path,
+        depth,
+        &settings.ignore_patterns,
+        &settings.language_overrides,
+    )
+    .await
+}
+
+/// Ignore files in the workspace with lines that couldn't be parsed.
+#[tauri::command]
+async fn get_ignore_file_errors(
+    app_handle: AppHandle,
+    dir_path: String,
+) -> Result<Vec<IgnoreFileError>, String> {
+    let settings = settings_service::load_application_settings_internal(&app_handle);
+    tokio::task::spawn_blocking(move || {
+        ignore_service::ignore_file_errors(Path::new(&dir_path), &settings.ignore_patterns)
+    })
+    .await
+    .map_err(|e| format!("Ignore file check failed: {}", e))?
 }
 
 /// Calculate tokens for a specific file.
@@ -92,6 +203,27 @@ async fn calculate_tokens_for_files(
     return token_service::calculate_tokens_for_files(file_paths, &app_handle, &cache_state).await;
 }
 
+// Calculate tokens for file selections, counting only the chosen lines of partial ones
+#[tauri::command]
+async fn calculate_tokens_for_selections(
+    selections: Vec<FileSelection>,
+    app_handle: AppHandle,
+    cache_state: State<'_, cache_service::CacheState>,
+) -> Result<HashMap<String, usize>, String> {
+    token_service::calculate_tokens_for_selections(&selections, &app_handle, &cache_state).await
+}
+
+#[tauri::command]
+async fn calculate_transform_savings(
+    selections: Vec<FileSelection>,
+    transforms: ContentTransforms,
+    app_handle: AppHandle,
+    cache_state: State<'_, cache_service::CacheState>,
+) -> Result<TransformSavings, String> {
+    token_service::calculate_transform_savings(&selections, &transforms, &app_handle, &cache_state)
+        .await
+}
+
 #[tauri::command]
 async fn open_workspace(
     window: Window,
@@ -113,13 +245,26 @@ async fn open_workspace(
     // --- End License Check ---
 
     // First, get the file tree
-    let tree = tree_service::get_file_tree(dir_path.clone(), false)
-        .await
-        .map_err(|e| ApiError::new("file_tree_error", &e))?;
+    let settings = settings_service::load_application_settings_internal(&app_handle);
+    let tree = tree_service::get_file_tree(
+        dir_path.clone(),
+        false,
+        &settings.ignore_patterns,
+        &settings.language_overrides,
+        settings.file_size_cap,
+        settings.tree_load_depth,
+    )
+    .await
+    .map_err(|e| ApiError::new("file_tree_error", &e))?;
 
     // Then, start the watcher for this directory
-    watcher_service::start_watcher_internal(window, dir_path, &watcher_state.0)
-        .map_err(|e| ApiError::new("watcher_error", &e))?;
+    watcher_service::start_watcher_internal(
+        window,
+        dir_path,
+        settings.ignore_patterns,
+        &watcher_state.0,
+    )
+    .map_err(|e| ApiError::new("watcher_error", &e))?;
 
     Ok(tree)
 }
@@ -209,13 +354,81 @@ async fn get_application_settings(app_handle: AppHandle) -> Result<ApplicationSe
     ))
 }
 
+/// Saves the settings; when the ignore patterns change, the open workspace's
+/// tree and watcher are rebuilt with them and the new tree is returned.
 #[tauri::command]
 async fn update_application_settings(
+    window: Window,
     app_handle: AppHandle,
+    watcher_state: State<'_, watcher_service::WatcherState>,
+    cache_state: State<'_, cache_service::CacheState>,
     settings: ApplicationSettings,
-) -> Result<(), String> {
+    workspace_path: Option<String>,
+) -> Result<Option<Vec<FileTreeNode>>, String> {
+    let previous = settings_service::load_application_settings_internal(&app_handle);
     // Pass the settings received from the frontend
-    settings_service::save_application_settings_internal(&app_handle, &settings)
+    settings_service::save_application_settings_internal(&app_handle, &settings)?;
+    // Cached token counts were clamped and estimated under the old cap
+    if previous.file_size_cap != settings.file_size_cap {
+        cache_service::clear_cache_internal(&app_handle, &cache_state)?;
+    }
+
+    // The open workspace's tree and watcher were built with the old patterns
+    let Some(dir_path) = workspace_path else {
+        return Ok(None);
+    };
+    if previous.ignore_patterns == settings.ignore_patterns {
+        return Ok(None);
+    }
+    let tree = tree_service::get_file_tree(
+        dir_path.clone(),
+        false,
+        &settings.ignore_patterns,
+        &settings.language_overrides,
+        settings.file_size_cap,
+        settings.tree_load_depth,
+    )
+    .await?;
+    watcher_service::start_watcher_internal(
+        window,
+        dir_path,
+        settings.ignore_patterns,
+        &watcher_state.0,
+    )?;
+    Ok(Some(tree))
+}
+
+/// The ignore patterns new installs start with, for resetting the edited ones.
+#[tauri::command]
+async fn get_default_ignore_patterns() -> Vec<String> {
+    application_settings::default_ignore_patterns()
+}
+
+#[tauri::command]
+async fn get_prompt_templates(app_handle: AppHandle) -> Result<Vec<PromptTemplate>, String> {
+    Ok(settings_service::load_prompt_templates_internal(
+        &app_handle,
+    ))
+}
+
+#[tauri::command]
+async fn update_prompt_templates(
+    app_handle: AppHandle,
+    templates: Vec<PromptTemplate>,
+) -> Result<(), String> {
+    // Rejects templates with unknown placeholders or duplicate names
+    settings_service::save_prompt_templates_internal(&app_handle, &templates)
+}
+
+/// Checks custom redaction rules before they're saved, so the settings screen
+/// can show which pattern doesn't compile.
+#[tauri::command]
+async fn validate_redaction_rules(rules: Vec<RedactionRule>) -> Result<(), String> {
+    export::Redactor::new(&RedactionSettings {
+        enabled: true,
+        custom_rules: rules,
+    })
+    .map(|_| ())
 }
 // --- End Settings Commands ---
 
@@ -253,9 +466,16 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             copy_files_with_tree_to_clipboard,
+            split_export_into_parts,
+            copy_text_to_clipboard,
+            export_to_file,
             calculate_file_tokens,
             calculate_tokens_for_files,
+            calculate_tokens_for_selections,
+            calculate_transform_savings,
             get_file_tree,
+            get_directory_children,
+            get_ignore_file_errors,
             open_workspace,
             close_workspace,
             activate_license,
@@ -263,6 +483,10 @@ pub fn run() {
             check_workspace_limit,
             get_application_settings,
             update_application_settings,
+            get_default_ignore_patterns,
+            get_prompt_templates,
+            update_prompt_templates,
+            validate_redaction_rules,
             watcher_service::start_watching_command,
             watcher_service::stop_watching_command,
             clear_cache,
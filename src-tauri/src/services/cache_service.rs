@@ -22,6 +22,11 @@ pub struct CacheEntry {
 
 pub type Cache = LruCache<String, CacheEntry>;
 
+/// Bumped whenever the way files are counted changes, so counts persisted by
+/// an older build are thrown away. Version 2: UTF-16 files are decoded instead
+/// of being counted as 0-token binaries.
+const CACHE_VERSION: u64 = 2;
+
 pub struct CacheState(pub Mutex<Cache>);
 
 // Helper to get seconds since epoch from SystemTime
@@ -47,6 +52,15 @@ pub fn load_cache(app_handle: &AppHandle) -> Cache {
 
     match app_handle.store(path) {
         Ok(store) => {
+            let version = store.get("token_cache_version").and_then(|v| v.as_u64());
+            if version != Some(CACHE_VERSION) && store.has("token_cache") {
+                info!(
+                    "Token cache version {:?} is outdated (current {}). Starting fresh.",
+                    version, CACHE_VERSION
+                );
+                store.delete("token_cache");
+                store.save().ok();
+            }
             if let Some(cache_data) = store.get("token_cache") {
                 match serde_json::from_value::<HashMap<String, CacheEntry>>(cache_data.clone()) {
                     Ok(loaded_map) => {
@@ -108,6 +122,7 @@ pub fn save_cache(
                 .map_err(|e| format!("Failed to serialize cache: {}", e))?;
 
             store.set("token_cache".to_string(), cache_data);
+            store.set("token_cache_version".to_string(), CACHE_VERSION);
 
             store
                 .save()
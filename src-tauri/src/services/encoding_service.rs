@@ -0,0 +1,322 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+// How much of a file the sniffing looks at
+const SNIFF_BYTES: usize = 8192;
+// How much of it the binary check looks at
+const BINARY_CHECK_BYTES: usize = 2048;
+
+/// Reads a text file in whatever encoding it was written in and returns it as
+/// UTF-8. A byte order mark decides the encoding when there is one, valid
+/// UTF-8 stays UTF-8, and anything else is guessed from its content.
+pub fn read_text_file(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    let encoding = detect_encoding(&bytes, true);
+    // Invalid sequences become U+FFFD rather than failing the whole file
+    let (text, _) = encoding.decode_with_bom_removal(&bytes);
+    Ok(text.into_owned())
+}
+
+/// The first few KB of a file, enough to tell its encoding or whether it's
+/// binary.
+pub fn read_prefix(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The encoding of a text file judging by its first few KB, `None` when the
+/// file can't be read or looks binary.
+pub fn detect_file_encoding(path: &Path) -> Option<&'static Encoding> {
+    let bytes = read_prefix(path).ok()?;
+    if looks_binary(&bytes) {
+        return None;
+    }
+    let complete = bytes.len() < SNIFF_BYTES;
+    Some(detect_encoding(&bytes, complete))
+}
+
+/// Whether bytes from the start of a file look like binary data: any NUL byte
+/// or more than 10% control characters. UTF-16 text is full of NUL bytes, so
+/// it's recognised first.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let bytes = &bytes[..bytes.len().min(BINARY_CHECK_BYTES)];
+    if bytes.is_empty() || sniff_utf16(bytes).is_some() {
+        return false;
+    }
+
+    let mut control_count = 0;
+    for &b in bytes {
+        if b == 0 {
+            // immediate giveaway
+            return true;
+        }
+        if b < 32 && b != b'\n' && b != b'\r' && b != b'\t' {
+            control_count += 1;
+        }
+    }
+    control_count as f64 / bytes.len() as f64 > 0.10
+}
+
+// `complete` says whether `bytes` is the whole file, so a multi-byte sequence
+// cut off at the end isn't held against UTF-8
+fn detect_encoding(bytes: &[u8], complete: bool) -> &'static Encoding {
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return encoding;
+    }
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return UTF_8,
+        Err(e) if !complete && e.error_len().is_none() => return UTF_8,
+        Err(_) => {}
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, complete);
+    detector.guess(None, false)
+}
+
+// UTF-16 by its byte order mark, or without one by the NUL byte that goes
+// with every ASCII character, on odd offsets for little endian and even ones
+// for big endian
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    match bytes {
+        [0xFF, 0xFE, ..] => return Some(UTF_16LE),
+        [0xFE, 0xFF, ..] => return Some(UTF_16BE),
+        _ => {}
+    }
+
+    let pairs = bytes.len() / 2;
+    if pairs < 4 {
+        return None;
+    }
+    let even_nuls = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    // Mostly ASCII on one side, hardly any NULs on the other
+    if odd_nuls * 10 >= pairs * 7 && even_nuls * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 10 >= pairs * 7 && odd_nuls * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// The first and last lines of a file too big to read in full.
+pub struct HeadAndTail {
+    pub head: Vec<String>,
+    pub tail: Vec<String>,
+    pub total_lines: usize,
+    /// Size in UTF-8 of everything between head and tail, including the
+    /// parts of overlong lines that were dropped.
+    pub skipped_bytes: u64,
+}
+
+/// Streams a text file, keeping only its first `head_lines` and last
+/// `tail_lines` lines, each side holding at most `max_bytes / 2` of text, so
+/// memory use doesn't grow with the file.
+pub fn read_head_and_tail(
+    path: &Path,
+    head_lines: usize,
+    tail_lines: usize,
+    max_bytes: u64,
+) -> io::Result<HeadAndTail> {
+    let mut file = File::open(path)?;
+    let mut chunk = Vec::with_capacity(SNIFF_BYTES);
+    (&mut file)
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut chunk)?;
+    let mut decoder = detect_encoding(&chunk, false).new_decoder_with_bom_removal();
+
+    let mut lines = LineCollector {
+        head_lines,
+        tail_lines,
+        side_budget: (max_bytes / 2).max(1) as usize,
+        head: Vec::new(),
+        head_bytes: 0,
+        head_closed: false,
+        tail: VecDeque::new(),
+        tail_bytes: 0,
+        current: String::new(),
+        total_lines: 0,
+        skipped_bytes: 0,
+    };
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let last = chunk.is_empty();
+        let mut decoded =
+            String::with_capacity(decoder.max_utf8_buffer_length(chunk.len()).unwrap_or(0));
+        let _ = decoder.decode_to_string(&chunk, &mut decoded, last);
+        lines.push_str(&decoded);
+        if last {
+            break;
+        }
+        let read = file.read(&mut buffer)?;
+        chunk.clear();
+        chunk.extend_from_slice(&buffer[..read]);
+    }
+    Ok(lines.finish())
+}
+
+struct LineCollector {
+    head_lines: usize,
+    tail_lines: usize,
+    // Most bytes of text kept on each side
+    side_budget: usize,
+    head: Vec<String>,
+    head_bytes: usize,
+    // Set once a line didn't go into the head, every later line goes to the tail
+    head_closed: bool,
+    tail: VecDeque<String>,
+    tail_bytes: usize,
+    current: String,
+    total_lines: usize,
+    skipped_bytes: u64,
+}
+
+impl LineCollector {
+    fn push_str(&mut self, text: &str) {
+        for segment in text.split_inclusive('\n') {
+            let (content, ends_line) = match segment.strip_suffix('\n') {
+                Some(content) => (content, true),
+                None => (segment, false),
+            };
+            // Overlong lines keep their start, the rest counts as skipped
+            let room = self.side_budget.saturating_sub(self.current.len());
+            let keep = content
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(|end| *end <= room)
+                .last()
+                .unwrap_or(0);
+            self.current.push_str(&content[..keep]);
+            self.skipped_bytes += (content.len() - keep) as u64;
+            if ends_line {
+                self.end_line();
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        let mut line = std::mem::take(&mut self.current);
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        self.total_lines += 1;
+
+        if !self.head_closed
+            && self.head.len() < self.head_lines
+            && self.head_bytes + line.len() <= self.side_budget
+        {
+            self.head_bytes += line.len();
+            self.head.push(line);
+            return;
+        }
+        self.head_closed = true;
+
+        self.tail_bytes += line.len();
+        self.tail.push_back(line);
+        while self.tail.len() > self.tail_lines
+            || (self.tail_bytes > self.side_budget && self.tail.len() > 1)
+        {
+            if let Some(dropped) = self.tail.pop_front() {
+                self.tail_bytes -= dropped.len();
+                self.skipped_bytes += dropped.len() as u64 + 1;
+            }
+        }
+    }
+
+    fn finish(mut self) -> HeadAndTail {
+        if !self.current.is_empty() {
+            self.end_line();
+        }
+        HeadAndTail {
+            head: self.head,
+            tail: self.tail.into(),
+            total_lines: self.total_lines,
+            skipped_bytes: self.skipped_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+    use std::path::PathBuf;
+
+    const FRENCH: &str =
+        "Café crème brûlée, déjà vu à la française.\nÇa coûte très cher, garçon !\n";
+    const JAPANESE: &str = "日本語のテキストです。\nこれはシフトJISで書かれたファイルです。\n";
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian {
+            vec![0xFE, 0xFF]
+        } else {
+            vec![0xFF, 0xFE]
+        };
+        for unit in text.encode_utf16() {
+            let pair = if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            };
+            bytes.extend_from_slice(&pair);
+        }
+        bytes
+    }
+
+    // Writes every fixture to a temp dir and returns its path
+    fn write_fixtures(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("treesnap-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("utf16le.txt"), utf16(FRENCH, false)).unwrap();
+        fs::write(dir.join("utf16be.txt"), utf16(FRENCH, true)).unwrap();
+        fs::write(dir.join("latin1.txt"), WINDOWS_1252.encode(FRENCH).0).unwrap();
+        fs::write(dir.join("shift_jis.txt"), SHIFT_JIS.encode(JAPANESE).0).unwrap();
+        fs::write(dir.join("utf8.txt"), JAPANESE).unwrap();
+        dir
+    }
+
+    #[test]
+    fn files_are_decoded_from_their_encoding() {
+        let dir = write_fixtures("encodings");
+        let read = |name: &str| {
+            let path = dir.join(name);
+            let encoding = detect_file_encoding(&path).map(Encoding::name);
+            (encoding, read_text_file(&path).unwrap())
+        };
+        let utf16le = read("utf16le.txt");
+        let utf16be = read("utf16be.txt");
+        let latin1 = read("latin1.txt");
+        let shift_jis = read("shift_jis.txt");
+        let utf8 = read("utf8.txt");
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The byte order mark isn't part of the text
+        assert_eq!(utf16le, (Some("UTF-16LE"), FRENCH.to_string()));
+        assert_eq!(utf16be, (Some("UTF-16BE"), FRENCH.to_string()));
+        // encoding_rs treats Latin-1 as its superset windows-1252, as browsers do
+        assert_eq!(latin1, (Some("windows-1252"), FRENCH.to_string()));
+        assert_eq!(shift_jis, (Some("Shift_JIS"), JAPANESE.to_string()));
+        assert_eq!(utf8, (Some("UTF-8"), JAPANESE.to_string()));
+    }
+
+    #[test]
+    fn utf16_without_bom_is_text() {
+        let bytes = utf16(FRENCH, false);
+        assert!(!looks_binary(&bytes[2..]));
+        assert_eq!(detect_encoding(&bytes[2..], true), UTF_16LE);
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01\0\0\0"));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::domain::export_summary::ExportSummary;
+use crate::services::{export::ExportPlan, file_service};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+use tiktoken_rs::CoreBPE;
+use tracing::info;
+
+/// Streams the export for the planned files into `output_path`.
+///
+/// Each rendered chunk goes straight to a buffered writer, so at most one file's
+/// content is held in memory at a time. Tokens are counted per chunk, which can
+/// differ by a handful from encoding the whole document in one pass.
+pub fn export_to_file(
+    output_path: &Path,
+    plan: &ExportPlan,
+    bpe: &CoreBPE,
+) -> Result<ExportSummary, String> {
+    let file = File::create(output_path).map_err(|e| {
+        format!(
+            "Failed to create export file {}: {}",
+            output_path.display(),
+            e
+        )
+    })?;
+    let mut writer = BufWriter::new(file);
+    let mut bytes_written: u64 = 0;
+    let mut token_count = 0;
+
+    let (file_count, redactions) = file_service::write_file_content(plan, bpe, |chunk| {
+        writer
+            .write_all(chunk.as_bytes())
+            .map_err(|e| format!("Failed to write export file: {}", e))?;
+        bytes_written += chunk.len() as u64;
+        token_count += bpe.encode_with_special_tokens(chunk).len();
+        Ok(())
+    })?;
+
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush export file: {}", e))?;
+
+    info!(
+        "Exported {} files to {} ({} bytes, {} tokens)",
+        file_count,
+        output_path.display(),
+        bytes_written,
+        token_count
+    );
+
+    Ok(ExportSummary {
+        path: output_path.to_string_lossy().to_string(),
+        file_count,
+        bytes_written,
+        token_count,
+        omitted_files: plan.omitted.clone(),
+        redactions,
+    })
+}
@@ -0,0 +1,541 @@
+use crate::domain::{export_format::ExportFormat, token_budget::OmittedFile};
+use serde::Serialize;
+use std::borrow::Cow;
+
+use super::metadata::ExportMetadata;
+
+const PLAIN_TEXT_RULE: &str =
+    "================================================================================";
+
+/// What the exporter could make of a file on disk.
+pub enum FileBody<'a> {
+    Text(&'a str),
+    Binary,
+    Unreadable,
+}
+
+impl FileBody<'_> {
+    // Text placed in the export for this body, placeholders included
+    fn as_export_text(&self) -> &str {
+        match self {
+            FileBody::Text(content) => content,
+            FileBody::Binary => "[Binary file]\n",
+            FileBody::Unreadable => "[Could not read content]\n",
+        }
+    }
+}
+
+/// A single file as handed to a renderer.
+pub struct ExportFile<'a> {
+    pub path: &'a str,
+    pub language: &'a str,
+    pub body: FileBody<'a>,
+}
+
+/// Renders an export document piece by piece.
+///
+/// Each call returns the next chunk of the document so callers can either
+/// concatenate them or stream them straight to their destination.
+pub trait ExportRenderer {
+    /// Opening of the document, including the metadata header and the file
+    /// map when they are given.
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String;
+    /// One file entry.
+    fn file(&mut self, file: &ExportFile) -> String;
+    /// Closing of the document, listing any files left out of the export.
+    fn end(&mut self, omitted: &[OmittedFile]) -> String;
+}
+
+pub fn renderer_for(format: ExportFormat) -> Box<dyn ExportRenderer + Send> {
+    match format {
+        ExportFormat::Classic => Box::new(ClassicRenderer),
+        ExportFormat::Xml => Box::new(XmlRenderer),
+        ExportFormat::Markdown => Box::new(MarkdownRenderer),
+        ExportFormat::Json => Box::new(JsonRenderer::default()),
+        ExportFormat::PlainText => Box::new(PlainTextRenderer),
+    }
+}
+
+/// Picks a backtick fence longer than any backtick run inside `text`, so a
+/// fence in the content (e.g. a Markdown file or a doc comment) can never
+/// close the block early. Never shorter than the usual three backticks.
+pub fn fence_for(text: &str) -> String {
+    let mut longest_run = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            run += 1;
+            longest_run = longest_run.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat((longest_run + 1).max(3))
+}
+
+// Ensure a chunk of file text ends with a newline before a closing marker
+fn push_terminated(output: &mut String, text: &str) {
+    output.push_str(text);
+    if !text.ends_with('\n') {
+        output.push('\n');
+    }
+}
+
+// Header fields as "Label: value" lines
+fn metadata_lines(metadata: &ExportMetadata, indent: &str) -> String {
+    metadata
+        .fields()
+        .into_iter()
+        .map(|(label, value)| format!("{}{}: {}\n", indent, label, value))
+        .collect()
+}
+
+// `<export_metadata>` and `<file_map>` blocks shared by the tag-based formats
+fn tagged_preamble(metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+    let mut output = String::new();
+    if let Some(metadata) = metadata {
+        output.push_str(&format!(
+            "<export_metadata>\n{}</export_metadata>\n\n",
+            metadata_lines(metadata, "")
+        ));
+    }
+    if let Some(map) = file_map {
+        output.push_str(&format!("<file_map>\n{}</file_map>\n\n", map));
+    }
+    output
+}
+
+// `<omitted_files>` block shared by the tag-based formats
+fn tagged_omitted_files(omitted: &[OmittedFile]) -> String {
+    if omitted.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("\n\n<omitted_files reason=\"token budget\">\n");
+    for file in omitted {
+        output.push_str(&format!("{} ({} tokens)\n", file.path, file.token_count));
+    }
+    output.push_str("</omitted_files>");
+    output
+}
+
+/// The original TreeSnap layout: "File: path" headers inside `<file_contents>`.
+struct ClassicRenderer;
+
+impl ExportRenderer for ClassicRenderer {
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+        let mut output = tagged_preamble(metadata, file_map);
+        output.push_str("<file_contents>\n");
+        output
+    }
+
+    fn file(&mut self, file: &ExportFile) -> String {
+        let text = file.body.as_export_text();
+        let fence = fence_for(text);
+        let mut output = format!("File: {}\n{}{}\n", file.path, fence, file.language);
+        push_terminated(&mut output, text);
+        output.push_str(&fence);
+        output.push_str("\n\n");
+        output
+    }
+
+    fn end(&mut self, omitted: &[OmittedFile]) -> String {
+        let mut output = "</file_contents>".to_string();
+        output.push_str(&tagged_omitted_files(omitted));
+        output
+    }
+}
+
+/// Every file wrapped in its own `<file path="...">` element.
+struct XmlRenderer;
+
+// Escape a value for use inside a double-quoted XML attribute
+fn escape_xml_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Wrap element content in CDATA when it contains something that would end the
+// `<file>` element early; a literal `]]>` is split across two CDATA sections
+fn escape_xml_content(text: &str) -> Cow<'_, str> {
+    if !text.contains("</file") && !text.contains("]]>") {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(format!(
+        "<![CDATA[{}]]>",
+        text.replace("]]>", "]]]]><![CDATA[>")
+    ))
+}
+
+impl ExportRenderer for XmlRenderer {
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+        let mut output = tagged_preamble(metadata, file_map);
+        output.push_str("<file_contents>\n");
+        output
+    }
+
+    fn file(&mut self, file: &ExportFile) -> String {
+        let mut output = format!(
+            "<file path=\"{}\" language=\"{}\">\n",
+            escape_xml_attr(file.path),
+            escape_xml_attr(file.language)
+        );
+        push_terminated(&mut output, &escape_xml_content(file.body.as_export_text()));
+        output.push_str("</file>\n\n");
+        output
+    }
+
+    fn end(&mut self, omitted: &[OmittedFile]) -> String {
+        let mut output = "</file_contents>".to_string();
+        output.push_str(&tagged_omitted_files(omitted));
+        output
+    }
+}
+
+/// Markdown headings with fenced code blocks and no XML-style tags.
+struct MarkdownRenderer;
+
+impl ExportRenderer for MarkdownRenderer {
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+        let mut output = String::new();
+        if let Some(metadata) = metadata {
+            output.push_str("## Export Info\n\n");
+            for (label, value) in metadata.fields() {
+                output.push_str(&format!("- **{}:** {}\n", label, value));
+            }
+            output.push('\n');
+        }
+        if let Some(map) = file_map {
+            let fence = fence_for(map);
+            output.push_str(&format!("## File Map\n\n{}\n", fence));
+            push_terminated(&mut output, map);
+            output.push_str(&fence);
+            output.push_str("\n\n");
+        }
+        output.push_str("## Files\n\n");
+        output
+    }
+
+    fn file(&mut self, file: &ExportFile) -> String {
+        let text = file.body.as_export_text();
+        let fence = fence_for(text);
+        let mut output = format!("### {}\n\n{}{}\n", file.path, fence, file.language);
+        push_terminated(&mut output, text);
+        output.push_str(&fence);
+        output.push_str("\n\n");
+        output
+    }
+
+    fn end(&mut self, omitted: &[OmittedFile]) -> String {
+        let mut output = String::new();
+        if !omitted.is_empty() {
+            output.push_str("## Omitted Files (token budget)\n\n");
+            for file in omitted {
+                output.push_str(&format!("- {} ({} tokens)\n", file.path, file.token_count));
+            }
+        }
+        output
+    }
+}
+
+/// A single JSON object: `{ "metadata": {...}, "file_map": ..., "files": [{path, language, content}], "omitted": [...] }`.
+#[derive(Default)]
+struct JsonRenderer {
+    files_written: usize,
+}
+
+#[derive(Serialize)]
+struct JsonFileEntry<'a> {
+    path: &'a str,
+    language: &'a str,
+    // `null` for binary or unreadable files, see `skipped`
+    content: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonMetadata<'a> {
+    workspace: &'a str,
+    exported_at: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<JsonRevision<'a>>,
+    file_count: usize,
+    token_count: usize,
+    tokenizer: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonRevision<'a> {
+    branch: Option<&'a str>,
+    commit: &'a str,
+    dirty: bool,
+}
+
+impl<'a> From<&'a ExportMetadata> for JsonMetadata<'a> {
+    fn from(metadata: &'a ExportMetadata) -> Self {
+        Self {
+            workspace: &metadata.workspace,
+            exported_at: &metadata.exported_at,
+            git: metadata.revision.as_ref().map(|revision| JsonRevision {
+                branch: revision.branch.as_deref(),
+                commit: &revision.commit,
+                dirty: revision.dirty,
+            }),
+            file_count: metadata.file_count,
+            token_count: metadata.token_count,
+            tokenizer: metadata.tokenizer,
+        }
+    }
+}
+
+// Serializing plain strings cannot fail, but keep the output valid JSON regardless
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "null".to_string())
+}
+
+impl ExportRenderer for JsonRenderer {
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+        let mut output = String::from("{\n");
+        if let Some(metadata) = metadata {
+            output.push_str(&format!(
+                "  \"metadata\": {},\n",
+                to_json(&JsonMetadata::from(metadata))
+            ));
+        }
+        if let Some(map) = file_map {
+            output.push_str(&format!("  \"file_map\": {},\n", to_json(&map)));
+        }
+        output.push_str("  \"files\": [");
+        output
+    }
+
+    fn file(&mut self, file: &ExportFile) -> String {
+        let (content, skipped) = match file.body {
+            FileBody::Text(content) => (Some(content), None),
+            FileBody::Binary => (None, Some("binary")),
+            FileBody::Unreadable => (None, Some("unreadable")),
+        };
+        let entry = JsonFileEntry {
+            path: file.path,
+            language: file.language,
+            content,
+            skipped,
+        };
+
+        let separator = if self.files_written == 0 { "\n" } else { ",\n" };
+        self.files_written += 1;
+        format!("{}    {}", separator, to_json(&entry))
+    }
+
+    fn end(&mut self, omitted: &[OmittedFile]) -> String {
+        let mut output = if self.files_written == 0 {
+            "]".to_string()
+        } else {
+            "\n  ]".to_string()
+        };
+        if !omitted.is_empty() {
+            output.push_str(&format!(",\n  \"omitted\": {}", to_json(&omitted)));
+        }
+        output.push_str("\n}\n");
+        output
+    }
+}
+
+/// Plain text with ruled separators, for tools that mangle Markdown or tags.
+struct PlainTextRenderer;
+
+// Content lines that read as the rule, or as a rule escaped this way, get one
+// more leading backslash, so only the exporter's own rules mark file boundaries
+fn escape_plain_text(text: &str) -> Cow<'_, str> {
+    let is_rule = |line: &str| {
+        line.trim_end_matches(['\n', '\r']).trim_start_matches('\\') == PLAIN_TEXT_RULE
+    };
+    if !text.split_inclusive('\n').any(is_rule) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.split_inclusive('\n')
+            .map(|line| {
+                if is_rule(line) {
+                    Cow::Owned(format!("\\{}", line))
+                } else {
+                    Cow::Borrowed(line)
+                }
+            })
+            .collect(),
+    )
+}
+
+impl ExportRenderer for PlainTextRenderer {
+    fn begin(&mut self, metadata: Option<&ExportMetadata>, file_map: Option<&str>) -> String {
+        let mut output = String::new();
+        if let Some(metadata) = metadata {
+            output.push_str("Export info:\n\n");
+            output.push_str(&metadata_lines(metadata, "  "));
+            output.push('\n');
+        }
+        if let Some(map) = file_map {
+            output.push_str("File map:\n\n");
+            push_terminated(&mut output, map);
+            output.push('\n');
+        }
+        output
+    }
+
+    fn file(&mut self, file: &ExportFile) -> String {
+        let mut output = format!(
+            "{}\nFile: {}\n{}\n",
+            PLAIN_TEXT_RULE, file.path, PLAIN_TEXT_RULE
+        );
+        push_terminated(&mut output, &escape_plain_text(file.body.as_export_text()));
+        output.push('\n');
+        output
+    }
+
+    fn end(&mut self, omitted: &[OmittedFile]) -> String {
+        let mut output = String::new();
+        if !omitted.is_empty() {
+            output.push_str("Omitted files (token budget):\n");
+            for file in omitted {
+                output.push_str(&format!("  {} ({} tokens)\n", file.path, file.token_count));
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::git_service::Revision;
+
+    fn render_one(format: ExportFormat, content: &str) -> String {
+        let mut renderer = renderer_for(format);
+        renderer.file(&ExportFile {
+            path: "README.md",
+            language: "md",
+            body: FileBody::Text(content),
+        })
+    }
+
+    fn metadata() -> ExportMetadata {
+        ExportMetadata {
+            workspace: "treesnap".to_string(),
+            exported_at: "2025-01-02T03:04:05+00:00".to_string(),
+            revision: Some(Revision {
+                branch: Some("main".to_string()),
+                commit: "0123abcd".to_string(),
+                dirty: true,
+            }),
+            file_count: 2,
+            token_count: 1500,
+            tokenizer: "o200k_base",
+        }
+    }
+
+    #[test]
+    fn classic_metadata_header_comes_before_the_file_map() {
+        let output = renderer_for(ExportFormat::Classic).begin(Some(&metadata()), Some("src\n"));
+        assert_eq!(
+            output,
+            "<export_metadata>\n\
+             Workspace: treesnap\n\
+             Exported: 2025-01-02T03:04:05+00:00\n\
+             Branch: main\n\
+             Commit: 0123abcd (uncommitted changes)\n\
+             Files: 2\n\
+             Tokens: 1500\n\
+             Tokenizer: o200k_base\n\
+             </export_metadata>\n\n\
+             <file_map>\nsrc\n</file_map>\n\n<file_contents>\n"
+        );
+    }
+
+    #[test]
+    fn json_metadata_header_is_an_object() {
+        let mut renderer = renderer_for(ExportFormat::Json);
+        let mut output = renderer.begin(Some(&metadata()), None);
+        output.push_str(&renderer.end(&[]));
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["metadata"]["git"]["commit"], "0123abcd");
+        assert_eq!(value["metadata"]["git"]["dirty"], true);
+        assert_eq!(value["metadata"]["token_count"], 1500);
+    }
+
+    #[test]
+    fn fence_defaults_to_three_backticks() {
+        assert_eq!(fence_for("fn main() {}\n"), "```");
+        assert_eq!(fence_for("a `tick` here"), "```");
+    }
+
+    #[test]
+    fn fence_outgrows_nested_fences() {
+        assert_eq!(fence_for("```rust\nfn main() {}\n```\n"), "````");
+        assert_eq!(fence_for("````md\n```\ninner\n```\n````\n"), "`````");
+    }
+
+    #[test]
+    fn classic_file_with_nested_fence_stays_closed() {
+        let content = "# Usage\n\n```rust\nfn main() {}\n```\n";
+        let output = render_one(ExportFormat::Classic, content);
+        assert_eq!(
+            output,
+            "File: README.md\n````md\n# Usage\n\n```rust\nfn main() {}\n```\n````\n\n"
+        );
+    }
+
+    #[test]
+    fn markdown_file_with_nested_fence_stays_closed() {
+        let content = "/// ```\n/// assert!(true);\n/// ```\n";
+        let output = render_one(ExportFormat::Markdown, content);
+        assert!(output.starts_with("### README.md\n\n````md\n"));
+        assert!(output.ends_with("/// ```\n````\n\n"));
+    }
+
+    #[test]
+    fn xml_content_closing_the_element_is_wrapped_in_cdata() {
+        let output = render_one(ExportFormat::Xml, "<file>x</file>\n]]>\n");
+        assert_eq!(
+            output,
+            "<file path=\"README.md\" language=\"md\">\n\
+             <![CDATA[<file>x</file>\n]]]]><![CDATA[>\n]]>\n</file>\n\n"
+        );
+    }
+
+    #[test]
+    fn xml_plain_content_is_left_alone() {
+        let output = render_one(ExportFormat::Xml, "let a = 1 < 2;\n");
+        assert!(output.contains("\nlet a = 1 < 2;\n</file>"));
+    }
+
+    #[test]
+    fn plain_text_content_cannot_fake_a_file_boundary() {
+        let content = format!("a\n{0}\nFile: secret.rs\n{0}\nb\n", PLAIN_TEXT_RULE);
+        let output = render_one(ExportFormat::PlainText, &content);
+        assert_eq!(
+            output,
+            format!(
+                "{0}\nFile: README.md\n{0}\na\n\\{0}\nFile: secret.rs\n\\{0}\nb\n\n",
+                PLAIN_TEXT_RULE
+            )
+        );
+        // Only the header's two rules are left as they are
+        assert_eq!(
+            output
+                .lines()
+                .filter(|line| *line == PLAIN_TEXT_RULE)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn plain_text_escaped_rules_get_another_backslash() {
+        let content = format!("\\{0}\r\n{0}=\n", PLAIN_TEXT_RULE);
+        let output = render_one(ExportFormat::PlainText, &content);
+        assert!(output.contains(&format!("\n\\\\{0}\r\n{0}=\n", PLAIN_TEXT_RULE)));
+    }
+}
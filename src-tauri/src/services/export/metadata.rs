@@ -0,0 +1,75 @@
+use crate::services::git_service::{self, Revision};
+use crate::services::{file_service, token_service};
+use chrono::{Local, SecondsFormat};
+use tiktoken_rs::CoreBPE;
+use tracing::debug;
+
+use super::plan::ExportPlan;
+
+/// Where an export came from, written at the top of it so a prompt can be
+/// traced back to the revision it was built from.
+pub struct ExportMetadata {
+    pub workspace: String,
+    /// RFC 3339 local time.
+    pub exported_at: String,
+    /// `None` when the workspace isn't in a git repository.
+    pub revision: Option<Revision>,
+    pub file_count: usize,
+    pub token_count: usize,
+    pub tokenizer: &'static str,
+}
+
+impl ExportMetadata {
+    /// Describes a planned export, which must not carry a header yet.
+    pub fn collect(dir_path: &str, plan: &ExportPlan, bpe: &CoreBPE) -> Self {
+        let revision = git_service::current_revision(dir_path)
+            .inspect_err(|e| debug!("No git revision for the export header: {}", e))
+            .ok();
+
+        let mut metadata = Self {
+            workspace: plan.workspace_name.clone(),
+            exported_at: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            revision,
+            file_count: 0,
+            token_count: 0,
+            tokenizer: token_service::TOKENIZER,
+        };
+        metadata.count(plan, bpe);
+        metadata
+    }
+
+    /// Counts the files and tokens of the plan again, e.g. once it's been
+    /// fitted into a token budget. The tokens are those of the export as
+    /// rendered, so the plan must not carry this header while it's counted.
+    pub fn count(&mut self, plan: &ExportPlan, bpe: &CoreBPE) {
+        self.file_count = plan.entries.len();
+        self.token_count = file_service::export_token_count(plan, bpe);
+    }
+
+    /// The header's fields as labelled values, in the order they're shown.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("Workspace", self.workspace.clone()),
+            ("Exported", self.exported_at.clone()),
+        ];
+        if let Some(revision) = &self.revision {
+            fields.push((
+                "Branch",
+                revision
+                    .branch
+                    .clone()
+                    .unwrap_or_else(|| "(detached HEAD)".to_string()),
+            ));
+            let dirty = if revision.dirty {
+                " (uncommitted changes)"
+            } else {
+                ""
+            };
+            fields.push(("Commit", format!("{}{}", revision.commit, dirty)));
+        }
+        fields.push(("Files", self.file_count.to_string()));
+        fields.push(("Tokens", self.token_count.to_string()));
+        fields.push(("Tokenizer", self.tokenizer.to_string()));
+        fields
+    }
+}
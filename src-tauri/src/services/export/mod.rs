@@ -0,0 +1,24 @@
+// Declare the sub-modules
+pub mod content;
+pub mod file_export;
+pub mod format;
+pub mod metadata;
+pub mod outline;
+pub mod paths;
+pub mod plan;
+pub mod redact;
+pub mod split;
+pub mod template;
+pub mod transform;
+
+// Re-export key items for easier access from `services::export::*`
+pub use content::{export_head_and_tail, export_text, ContentOptions};
+pub use file_export::export_to_file;
+pub use format::{renderer_for, ExportFile, FileBody};
+pub use paths::PathDisplay;
+pub use plan::{prepare_export, ExportPlan};
+pub use redact::Redactor;
+pub use split::split_export;
+pub use template::{
+    render_segment, template_segments, validate_templates, Segment, TemplateValues,
+};
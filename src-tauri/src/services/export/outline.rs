@@ -0,0 +1,320 @@
+use tree_sitter::{Language, Node, Parser};
+
+use super::content::FileContent;
+
+// Languages with an outline grammar
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutlineLanguage {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+}
+
+impl OutlineLanguage {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            // The TSX grammar also covers plain JavaScript and JSX
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    // Node kinds whose `body` field holds a function body
+    fn is_function(self, kind: &str) -> bool {
+        match self {
+            Self::Rust => kind == "function_item",
+            Self::TypeScript | Self::Tsx => matches!(
+                kind,
+                "function_declaration"
+                    | "function_expression"
+                    | "generator_function_declaration"
+                    | "generator_function"
+                    | "method_definition"
+                    | "arrow_function"
+            ),
+            Self::Python => kind == "function_definition",
+        }
+    }
+}
+
+/// Builds the outline of a source file: everything but function bodies, which
+/// become `{ ... }` (or `...` in Python, after the docstring). Returns `None`
+/// when the language has no grammar or the file doesn't parse cleanly, so the
+/// caller can fall back to the full content.
+///
+/// Lines keep their numbers from the original file.
+pub fn outline(source: &str, extension: &str) -> Option<FileContent> {
+    let language = OutlineLanguage::from_extension(extension)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(source, None)?;
+    let root = tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+
+    let mut replacements = Vec::new();
+    collect_body_replacements(root, language, &mut replacements);
+    Some(FileContent::from_numbered_lines(apply_replacements(
+        source,
+        &replacements,
+    )))
+}
+
+// A byte range of the source and the text that stands in for it
+struct Replacement {
+    start: usize,
+    end: usize,
+    text: &'static str,
+}
+
+// Walks the tree in source order; nested functions go with the body they're in
+fn collect_body_replacements(node: Node, language: OutlineLanguage, out: &mut Vec<Replacement>) {
+    if language.is_function(node.kind()) {
+        if let Some(replacement) = node
+            .child_by_field_name("body")
+            .and_then(|body| body_replacement(body, language))
+        {
+            out.push(replacement);
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_body_replacements(child, language, out);
+    }
+}
+
+fn body_replacement(body: Node, language: OutlineLanguage) -> Option<Replacement> {
+    match language {
+        // Expression-bodied arrow functions are already as short as a signature
+        OutlineLanguage::TypeScript | OutlineLanguage::Tsx if body.kind() != "statement_block" => {
+            None
+        }
+        OutlineLanguage::Python => {
+            let mut statements = body.named_children(&mut body.walk()).collect::<Vec<_>>();
+            // Keep the docstring, the statements after it become `...`
+            if statements.first().is_some_and(is_docstring) {
+                statements.remove(0);
+            }
+            let first = statements.first()?;
+            Some(Replacement {
+                start: first.start_byte(),
+                end: body.end_byte(),
+                text: "...",
+            })
+        }
+        _ => Some(Replacement {
+            start: body.start_byte(),
+            end: body.end_byte(),
+            text: "{ ... }",
+        }),
+    }
+}
+
+fn is_docstring(statement: &Node) -> bool {
+    statement.kind() == "expression_statement"
+        && statement
+            .named_child(0)
+            .is_some_and(|expression| expression.kind() == "string")
+}
+
+// Applies the replacements and splits the result into lines, each numbered by
+// the original line it starts on
+fn apply_replacements(source: &str, replacements: &[Replacement]) -> Vec<(usize, String)> {
+    let mut builder = LineBuilder {
+        lines: Vec::new(),
+        current: String::new(),
+        current_number: 1,
+        line_number: 1,
+    };
+    let mut position = 0;
+    for replacement in replacements {
+        builder.push_source(&source[position..replacement.start]);
+        builder.current.push_str(replacement.text);
+        builder.line_number += source[replacement.start..replacement.end]
+            .matches('\n')
+            .count();
+        position = replacement.end;
+    }
+    builder.push_source(&source[position..]);
+    if !builder.current.is_empty() {
+        builder
+            .lines
+            .push((builder.current_number, builder.current));
+    }
+    builder.lines
+}
+
+struct LineBuilder {
+    lines: Vec<(usize, String)>,
+    current: String,
+    // Original line the current output line started on
+    current_number: usize,
+    // Original line the source position is on
+    line_number: usize,
+}
+
+impl LineBuilder {
+    fn push_source(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                let line = std::mem::take(&mut self.current);
+                self.lines
+                    .push((self.current_number, line.trim_end_matches('\r').to_string()));
+                self.line_number += 1;
+                self.current_number = self.line_number;
+            } else {
+                self.current.push(c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, extension: &str) -> String {
+        outline(source, extension).unwrap().render()
+    }
+
+    #[test]
+    fn rust_bodies_collapse_and_signatures_stay() {
+        let source = "\
+/// Adds things.
+pub struct Adder {
+    pub offset: i32,
+}
+
+impl Adder {
+    /// Adds the offset.
+    pub fn add(&self, x: i32) -> i32 {
+        let y = x + self.offset;
+        y
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+";
+        assert_eq!(
+            render(source, "rs"),
+            "\
+/// Adds things.
+pub struct Adder {
+    pub offset: i32,
+}
+
+impl Adder {
+    /// Adds the offset.
+    pub fn add(&self, x: i32) -> i32 { ... }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+"
+        );
+    }
+
+    #[test]
+    fn outline_lines_keep_their_original_numbers() {
+        let source = "fn a() {\n    1;\n    2;\n}\nfn b() {}\n";
+        let mut file = outline(source, "rs").unwrap();
+        file.number_lines();
+        assert_eq!(file.render(), "1 | fn a() { ... }\n5 | fn b() { ... }\n");
+    }
+
+    #[test]
+    fn typescript_keeps_types_and_short_arrows() {
+        let source = "\
+interface Point {
+  x: number;
+}
+
+/** Distance from the origin. */
+export function length(p: Point): number {
+  return Math.hypot(p.x, 0);
+}
+
+const double = (n: number) => n * 2;
+
+class Counter {
+  count = 0;
+  increment(): void {
+    this.count++;
+  }
+}
+";
+        assert_eq!(
+            render(source, "ts"),
+            "\
+interface Point {
+  x: number;
+}
+
+/** Distance from the origin. */
+export function length(p: Point): number { ... }
+
+const double = (n: number) => n * 2;
+
+class Counter {
+  count = 0;
+  increment(): void { ... }
+}
+"
+        );
+    }
+
+    #[test]
+    fn python_keeps_docstrings() {
+        let source = "\
+class Greeter:
+    \"\"\"Says hello.\"\"\"
+
+    def greet(self, name: str) -> str:
+        \"\"\"Greets someone.\"\"\"
+        message = f\"Hello {name}\"
+        return message
+
+def helper():
+    return 1
+";
+        assert_eq!(
+            render(source, "py"),
+            "\
+class Greeter:
+    \"\"\"Says hello.\"\"\"
+
+    def greet(self, name: str) -> str:
+        \"\"\"Greets someone.\"\"\"
+        ...
+
+def helper():
+    ...
+"
+        );
+    }
+
+    #[test]
+    fn unsupported_or_broken_files_have_no_outline() {
+        assert!(outline("fn main() {}\n", "go").is_none());
+        assert!(outline("fn main( {\n", "rs").is_none());
+    }
+}
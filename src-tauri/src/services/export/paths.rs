@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+/// How file paths are written into an export.
+///
+/// With relative paths on, every path is written relative to the workspace
+/// root and the root itself goes by a display name, so exports don't leak the
+/// local directory layout or user name.
+pub struct PathDisplay {
+    root: PathBuf,
+    root_name: String,
+    relative: bool,
+}
+
+impl PathDisplay {
+    pub fn new(dir_path: &str, relative: bool, root_display_name: Option<&str>) -> Self {
+        let root = PathBuf::from(dir_path);
+        let root_name = root_display_name
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                root.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or(dir_path)
+                    .to_string()
+            });
+        Self {
+            root,
+            root_name,
+            relative,
+        }
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.relative
+    }
+
+    /// Name of the workspace root: the display name if one is set, the
+    /// directory's name otherwise.
+    pub fn root_name(&self) -> &str {
+        &self.root_name
+    }
+
+    /// Label for the top line of the file map.
+    pub fn root_label(&self) -> String {
+        if self.relative {
+            self.root_name.clone()
+        } else {
+            self.root.to_string_lossy().to_string()
+        }
+    }
+
+    /// The path as written into the export. Paths outside the workspace are
+    /// left as they are.
+    pub fn display(&self, path: &str) -> String {
+        if !self.relative {
+            return path.to_string();
+        }
+        match Path::new(path).strip_prefix(&self.root) {
+            // Always use forward slashes so exports read the same on every OS
+            Ok(rel) => rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => path.to_string(),
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use crate::domain::{
+    content_transforms::ContentTransforms,
+    export_format::ExportFormat,
+    export_options::ExportOptions,
+    file_selection::{ContentMode, FileSelection, LineRange},
+    file_size_cap::FileSizeCap,
+    prompt_template::PromptTemplate,
+    token_budget::{BudgetPriority, OmittedFile, TokenBudget},
+};
+use crate::services::cache_service;
+use crate::services::ignore_service::WorkspaceIgnore;
+use crate::services::{file_service, git_service, settings_service, token_service, tree_service};
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+use tauri::AppHandle;
+use tiktoken_rs::CoreBPE;
+use tracing::{debug, warn};
+
+use super::{metadata::ExportMetadata, paths::PathDisplay, redact::Redactor};
+
+/// A selected file scheduled for export.
+#[derive(Clone)]
+pub struct ExportEntry {
+    pub path: String,
+    /// The path as written into the export, see `PathDisplay`.
+    pub display_path: String,
+    /// Lines to keep; empty for the whole file.
+    pub line_ranges: Vec<LineRange>,
+    pub mode: ContentMode,
+    /// Content past this many tokens is cut off with a marker.
+    pub token_limit: Option<usize>,
+}
+
+impl ExportEntry {
+    fn new(selection: &FileSelection, paths: &PathDisplay) -> Self {
+        Self {
+            path: selection.path().to_string(),
+            display_path: paths.display(selection.path()),
+            line_ranges: selection.line_ranges().to_vec(),
+            mode: selection.mode(),
+            token_limit: None,
+        }
+    }
+}
+
+/// Everything decided about an export before rendering: its format, file map,
+/// content transforms, the files it will contain, and the ones it had to leave out.
+pub struct ExportPlan {
+    pub format: ExportFormat,
+    /// Header on top of the export; `None` unless enabled for the format.
+    pub metadata: Option<ExportMetadata>,
+    pub file_map: Option<String>,
+    pub transforms: ContentTransforms,
+    /// Prefix every exported line with its number in the file.
+    pub line_numbers: bool,
+    /// Cuts oversized files down to their first and last lines.
+    pub size_cap: FileSizeCap,
+    /// Diffs of the selected files by path, for diff exports. Files that aren't
+    /// in the map have no changes.
+    pub diffs: Option<HashMap<String, String>>,
+    /// Prompt template the export is wrapped in, with the values only the
+    /// caller knows.
+    pub template: Option<PromptTemplate>,
+    pub instructions: String,
+    pub workspace_name: String,
+    /// Code fence languages that replace the detected ones, see
+    /// `language_service::detect_language`.
+    pub language_overrides: HashMap<String, String>,
+    /// Replaces secrets in the exported content; `None` when redaction is off.
+    pub redactor: Option<Redactor>,
+    pub entries: Vec<ExportEntry>,
+    pub omitted: Vec<OmittedFile>,
+}
+
+impl ExportPlan {
+    /// Every selected file, in selection order, with nothing left out.
+    pub fn from_selections(
+        format: ExportFormat,
+        selections: &[FileSelection],
+        paths: &PathDisplay,
+    ) -> Self {
+        Self {
+            format,
+            metadata: None,
+            file_map: None,
+            transforms: ContentTransforms::default(),
+            line_numbers: false,
+            size_cap: FileSizeCap::default(),
+            diffs: None,
+            template: None,
+            instructions: String::new(),
+            workspace_name: String::new(),
+            language_overrides: HashMap::new(),
+            redactor: None,
+            entries: selections
+                .iter()
+                .map(|selection| ExportEntry::new(selection, paths))
+                .collect(),
+            omitted: Vec::new(),
+        }
+    }
+
+    /// Fits the planned files into `budget` using their known token counts,
+    /// moving the ones that don't fit to `omitted`.
+    ///
+    /// Files claim room in the budget's priority order, but the plan keeps
+    /// selection order so the export reads the same with or without a budget.
+    pub fn apply_budget(&mut self, token_counts: &HashMap<String, usize>, budget: &TokenBudget) {
+        let entries = std::mem::take(&mut self.entries);
+        let counts: Vec<usize> = entries
+            .iter()
+            .map(|entry| token_counts.get(&entry.path).copied().unwrap_or(0))
+            .collect();
+
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        match budget.priority {
+            BudgetPriority::SmallestFirst => order.sort_by_key(|&i| counts[i]),
+            BudgetPriority::SelectionOrder => {}
+            BudgetPriority::MostRecentlyModified => {
+                let modified: Vec<u64> = entries
+                    .iter()
+                    .map(|entry| cache_service::get_current_modified_secs(&entry.path).unwrap_or(0))
+                    .collect();
+                order.sort_by_key(|&i| Reverse(modified[i]));
+            }
+        }
+
+        // `None` marks a file that was left out, `Some(limit)` one that made it in
+        let mut admitted: Vec<Option<Option<usize>>> = vec![None; entries.len()];
+        let mut remaining = budget.max_tokens;
+        for i in order {
+            let count = counts[i];
+            if count <= remaining {
+                remaining -= count;
+                admitted[i] = Some(None);
+            } else if budget.truncate_overflow && remaining > 0 {
+                // Only the first file that overflows is truncated, it uses up the rest
+                admitted[i] = Some(Some(remaining));
+                remaining = 0;
+            }
+        }
+
+        for (i, mut entry) in entries.into_iter().enumerate() {
+            match admitted[i] {
+                Some(token_limit) => {
+                    entry.token_limit = token_limit;
+                    self.entries.push(entry);
+                }
+                None => self.omitted.push(OmittedFile {
+                    token_count: counts[i],
+                    path: entry.display_path,
+                }),
+            }
+        }
+    }
+
+    /// Fits the planned files into `budget`, counting each one as it's
+    /// rendered into the export, diffs for diff exports. What surrounds the
+    /// files, such as the metadata header, file map, prompt template and the
+    /// list of omitted files, is charged up front, and the export is rendered
+    /// again until all of it fits.
+    pub fn fit_to_budget(&mut self, budget: &TokenBudget, bpe: &CoreBPE) {
+        let token_counts = file_service::entry_token_counts(self, bpe);
+        let entries = std::mem::take(&mut self.entries);
+        // Everything but the files
+        let mut reserved = file_service::export_token_count(self, bpe);
+        loop {
+            self.entries = entries.clone();
+            self.omitted.clear();
+            let room = TokenBudget {
+                max_tokens: budget.max_tokens.saturating_sub(reserved),
+                ..budget.clone()
+            };
+            self.apply_budget(&token_counts, &room);
+
+            // A longer list of omitted files, or the markers of a truncated
+            // one, can still push the export over
+            let total = file_service::export_token_count(self, bpe);
+            if total <= budget.max_tokens || reserved >= budget.max_tokens {
+                break;
+            }
+            reserved += total - budget.max_tokens;
+        }
+    }
+}
+
+/// Resolves the export options against the saved settings and plans the
+/// export: builds the file map for `tree_option` and fits the selected files
+/// into the token budget when one is given, see `ExportPlan::fit_to_budget`.
+/// Selected files that are ignored by now, say after an edit to a
+/// `.treesnapignore`, are left out.
+pub async fn prepare_export(
+    app_handle: &AppHandle,
+    dir_path: &str,
+    selections: &[FileSelection],
+    tree_option: &str,
+    options: &ExportOptions,
+) -> Result<ExportPlan, String> {
+    let settings = settings_service::load_application_settings_internal(app_handle);
+    let ignore = WorkspaceIgnore::new(Path::new(dir_path), &settings.ignore_patterns)?;
+    let selections: Vec<FileSelection> = selections
+        .iter()
+        .filter(|selection| {
+            let ignored = ignore.is_ignored(Path::new(selection.path()), false);
+            if ignored {
+                debug!(
+                    "Leaving ignored file out of the export: {}",
+                    selection.path()
+                );
+            }
+            !ignored
+        })
+        .cloned()
+        .collect();
+    let selections = selections.as_slice();
+    // Fall back to the format saved in settings when none is picked for this export
+    let format = options.format.unwrap_or(settings.export_format);
+    let paths = PathDisplay::new(
+        dir_path,
+        settings.relative_paths,
+        settings.root_display_name.as_deref(),
+    );
+
+    let selected_file_paths: Vec<String> =
+        selections.iter().map(|s| s.path().to_string()).collect();
+    let file_map = tree_service::build_file_map(
+        dir_path,
+        &selected_file_paths,
+        tree_option,
+        &paths,
+        &settings,
+    )
+    .await?;
+
+    let with_metadata = settings.metadata_header_formats.contains(&format);
+    // Fall back to the budget saved in settings when none is given for this export
+    let token_budget = options
+        .token_budget
+        .clone()
+        .or_else(|| settings.token_budget.clone());
+    let mut plan = ExportPlan::from_selections(format, selections, &paths);
+    plan.file_map = file_map;
+    plan.transforms = options.transforms.unwrap_or(settings.transforms);
+    plan.line_numbers = options.line_numbers.unwrap_or(settings.line_numbers);
+    plan.size_cap = settings.file_size_cap;
+    if let Some(diff) = &options.diff {
+        plan.diffs = Some(git_service::diff_files(
+            dir_path,
+            &selected_file_paths,
+            diff,
+        )?);
+    }
+    plan.template = resolve_template(app_handle, options, settings.prompt_template.as_deref())?;
+    plan.instructions = options.instructions.clone().unwrap_or_default();
+    plan.workspace_name = paths.root_name().to_string();
+    plan.language_overrides = settings.language_overrides.clone();
+    if settings.redaction.enabled {
+        plan.redactor = Some(Redactor::new(&settings.redaction)?);
+    }
+
+    let bpe = token_service::shared_bpe()?;
+    // The header is part of what the budget has to fit, so it's there before
+    // the files are fitted, counting the export with every selected file
+    if with_metadata {
+        plan.metadata = Some(ExportMetadata::collect(dir_path, &plan, &bpe));
+    }
+    if let Some(budget) = &token_budget {
+        plan.fit_to_budget(budget, &bpe);
+        // Counted without the header, which goes on top of what it counts
+        if let Some(mut metadata) = plan.metadata.take() {
+            metadata.count(&plan, &bpe);
+            plan.metadata = Some(metadata);
+        }
+    }
+    Ok(plan)
+}
+
+// A template asked for by name in the options must exist. One only remembered in
+// the settings may have been deleted since, in which case the plain export is used.
+fn resolve_template(
+    app_handle: &AppHandle,
+    options: &ExportOptions,
+    saved_template: Option<&str>,
+) -> Result<Option<PromptTemplate>, String> {
+    let (name, explicit) = match (options.template.as_deref(), saved_template) {
+        (Some(name), _) => (name, true),
+        (None, Some(name)) => (name, false),
+        (None, None) => return Ok(None),
+    };
+
+    let template = settings_service::load_prompt_templates_internal(app_handle)
+        .into_iter()
+        .find(|template| template.name == name);
+    match template {
+        Some(template) => Ok(Some(template)),
+        None if explicit => Err(format!("No prompt template named '{}'", name)),
+        None => {
+            warn!(
+                "Saved prompt template '{}' no longer exists, exporting without it",
+                name
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file_service;
+    use std::{
+        fs::{self, File},
+        time::{Duration, SystemTime},
+    };
+
+    fn plan(paths: &[&str]) -> ExportPlan {
+        let selections: Vec<FileSelection> = paths
+            .iter()
+            .map(|path| FileSelection::Path(path.to_string()))
+            .collect();
+        ExportPlan::from_selections(
+            ExportFormat::Classic,
+            &selections,
+            &PathDisplay::new("/ws", true, None),
+        )
+    }
+
+    fn counts(counts: &[(&str, usize)]) -> HashMap<String, usize> {
+        counts
+            .iter()
+            .map(|(path, count)| (path.to_string(), *count))
+            .collect()
+    }
+
+    fn budget(max_tokens: usize, priority: BudgetPriority, truncate_overflow: bool) -> TokenBudget {
+        TokenBudget {
+            max_tokens,
+            priority,
+            truncate_overflow,
+        }
+    }
+
+    fn entry_paths(plan: &ExportPlan) -> Vec<&str> {
+        plan.entries
+            .iter()
+            .map(|entry| entry.display_path.as_str())
+            .collect()
+    }
+
+    fn omitted_paths(plan: &ExportPlan) -> Vec<&str> {
+        plan.omitted.iter().map(|file| file.path.as_str()).collect()
+    }
+
+    #[test]
+    fn smallest_first_fits_the_most_files() {
+        let mut plan = plan(&["/ws/big.rs", "/ws/small.rs", "/ws/medium.rs"]);
+        let counts = counts(&[
+            ("/ws/big.rs", 600),
+            ("/ws/small.rs", 100),
+            ("/ws/medium.rs", 300),
+        ]);
+        plan.apply_budget(&counts, &budget(500, BudgetPriority::SmallestFirst, false));
+
+        // Selection order is kept for the files that made it in
+        assert_eq!(entry_paths(&plan), ["small.rs", "medium.rs"]);
+        assert_eq!(omitted_paths(&plan), ["big.rs"]);
+        assert_eq!(plan.omitted[0].token_count, 600);
+    }
+
+    #[test]
+    fn selection_order_drops_the_last_files() {
+        let mut plan = plan(&["/ws/a.rs", "/ws/b.rs", "/ws/c.rs"]);
+        let counts = counts(&[("/ws/a.rs", 300), ("/ws/b.rs", 300), ("/ws/c.rs", 100)]);
+        plan.apply_budget(&counts, &budget(500, BudgetPriority::SelectionOrder, false));
+
+        // c.rs still fits once b.rs is dropped
+        assert_eq!(entry_paths(&plan), ["a.rs", "c.rs"]);
+        assert_eq!(omitted_paths(&plan), ["b.rs"]);
+    }
+
+    #[test]
+    fn selection_order_truncates_the_first_file_that_overflows() {
+        let mut plan = plan(&["/ws/a.rs", "/ws/b.rs", "/ws/c.rs"]);
+        let counts = counts(&[("/ws/a.rs", 300), ("/ws/b.rs", 300), ("/ws/c.rs", 100)]);
+        plan.apply_budget(&counts, &budget(500, BudgetPriority::SelectionOrder, true));
+
+        assert_eq!(entry_paths(&plan), ["a.rs", "b.rs"]);
+        assert_eq!(plan.entries[0].token_limit, None);
+        assert_eq!(plan.entries[1].token_limit, Some(200));
+        assert_eq!(omitted_paths(&plan), ["c.rs"]);
+    }
+
+    #[test]
+    fn most_recently_modified_keeps_the_newest_files() {
+        let root = std::env::temp_dir().join(format!("treesnap-budget-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let now = SystemTime::now();
+        let mut paths = Vec::new();
+        for (name, age_days) in [("old.rs", 30), ("new.rs", 0), ("older.rs", 60)] {
+            let path = root.join(name);
+            let file = File::create(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age_days * 86_400))
+                .unwrap();
+            paths.push(path.to_string_lossy().into_owned());
+        }
+
+        let selections: Vec<FileSelection> =
+            paths.iter().cloned().map(FileSelection::Path).collect();
+        let mut plan = ExportPlan::from_selections(
+            ExportFormat::Classic,
+            &selections,
+            &PathDisplay::new(&root.to_string_lossy(), true, None),
+        );
+        let counts: HashMap<String, usize> = paths.iter().map(|path| (path.clone(), 200)).collect();
+        plan.apply_budget(
+            &counts,
+            &budget(400, BudgetPriority::MostRecentlyModified, false),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(entry_paths(&plan), ["old.rs", "new.rs"]);
+        assert_eq!(omitted_paths(&plan), ["older.rs"]);
+    }
+
+    // A workspace of three Rust files of different sizes
+    fn workspace(name: &str) -> (std::path::PathBuf, Vec<FileSelection>) {
+        let root = std::env::temp_dir().join(format!("treesnap-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let mut selections = Vec::new();
+        for (file, lines) in [("a.rs", 30), ("b.rs", 200), ("c.rs", 12)] {
+            let content: String = (1..=lines)
+                .map(|i| format!("pub fn f{}() -> u32 {{ {} }}\n", i, i * 7))
+                .collect();
+            let path = root.join(file);
+            fs::write(&path, content).unwrap();
+            selections.push(FileSelection::Path(path.to_string_lossy().into_owned()));
+        }
+        (root, selections)
+    }
+
+    #[test]
+    fn rendered_export_fits_the_budget() {
+        let (root, selections) = workspace("fit");
+        let bpe = token_service::shared_bpe().unwrap();
+        let formats = [
+            ExportFormat::Classic,
+            ExportFormat::Xml,
+            ExportFormat::Markdown,
+            ExportFormat::Json,
+            ExportFormat::PlainText,
+        ];
+        for format in formats {
+            for truncate_overflow in [false, true] {
+                let mut plan = ExportPlan::from_selections(
+                    format,
+                    &selections,
+                    &PathDisplay::new(&root.to_string_lossy(), true, None),
+                );
+                plan.file_map = Some("demo\n├── a.rs\n├── b.rs\n└── c.rs\n".to_string());
+                plan.line_numbers = true;
+                plan.template = Some(PromptTemplate {
+                    name: "Review".to_string(),
+                    body: "Review these files from {{workspace_name}}, about {{token_count}} \
+                           tokens.\n\n{{file_map}}\n{{file_contents}}\n{{instructions}}\n"
+                        .to_string(),
+                });
+                plan.instructions = "Point out anything that looks wrong.".to_string();
+                plan.workspace_name = "demo".to_string();
+                plan.metadata = Some(ExportMetadata::collect(
+                    &root.to_string_lossy(),
+                    &plan,
+                    &bpe,
+                ));
+
+                plan.fit_to_budget(
+                    &budget(800, BudgetPriority::SelectionOrder, truncate_overflow),
+                    &bpe,
+                );
+                let (output, _) = file_service::build_file_content_string(&plan, &bpe);
+                let tokens = bpe.encode_with_special_tokens(&output).len();
+                assert!(
+                    tokens <= 800,
+                    "{:?} truncate={}: {} tokens\n{}",
+                    format,
+                    truncate_overflow,
+                    tokens,
+                    output
+                );
+                assert!(entry_paths(&plan).contains(&"a.rs"), "{:?}", format);
+                assert!(
+                    !plan.omitted.is_empty() || truncate_overflow,
+                    "{:?}",
+                    format
+                );
+            }
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn header_counts_the_rendered_export() {
+        let (root, selections) = workspace("header-count");
+        let bpe = token_service::shared_bpe().unwrap();
+        let mut plan = ExportPlan::from_selections(
+            ExportFormat::Markdown,
+            &selections,
+            &PathDisplay::new(&root.to_string_lossy(), true, None),
+        );
+        plan.file_map = Some("demo\n├── a.rs\n├── b.rs\n└── c.rs\n".to_string());
+        plan.line_numbers = true;
+        let mut metadata = ExportMetadata::collect(&root.to_string_lossy(), &plan, &bpe);
+        let (body, _) = file_service::build_file_content_string(&plan, &bpe);
+        let expected = bpe.encode_with_special_tokens(&body).len();
+        // Counting chunk by chunk runs a few tokens over a single pass
+        assert!(
+            (expected..=expected + 5).contains(&metadata.token_count),
+            "{} vs {}",
+            metadata.token_count,
+            expected
+        );
+
+        plan.fit_to_budget(&budget(300, BudgetPriority::SelectionOrder, true), &bpe);
+        metadata.count(&plan, &bpe);
+        let (body, _) = file_service::build_file_content_string(&plan, &bpe);
+        let expected = bpe.encode_with_special_tokens(&body).len();
+        assert!((expected..=expected + 5).contains(&metadata.token_count));
+        assert!(metadata.token_count <= 300);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn diff_exports_are_fitted_by_their_diffs() {
+        let (root, selections) = workspace("fit-diff");
+        let bpe = token_service::shared_bpe().unwrap();
+        let mut plan = ExportPlan::from_selections(
+            ExportFormat::Classic,
+            &selections,
+            &PathDisplay::new(&root.to_string_lossy(), true, None),
+        );
+        // Small changes to files far bigger than the budget
+        plan.diffs = Some(
+            selections
+                .iter()
+                .map(|selection| {
+                    (
+                        selection.path().to_string(),
+                        "@@ -1 +1 @@\n-pub fn f1() -> u32 { 7 }\n+pub fn f1() -> u32 { 8 }\n"
+                            .to_string(),
+                    )
+                })
+                .collect(),
+        );
+        plan.fit_to_budget(&budget(300, BudgetPriority::SelectionOrder, false), &bpe);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(entry_paths(&plan), ["a.rs", "b.rs", "c.rs"]);
+        assert!(plan.omitted.is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::domain::export_parts::{ExportPart, ExportParts};
+use crate::services::{export::ExportPlan, file_service};
+use tiktoken_rs::CoreBPE;
+
+// Widest part numbers the header is measured with, so the real header never
+// takes more room than was set aside for it
+const WIDEST_PART_NUMBER: usize = 9999;
+
+// Parts need room for some content next to their header
+const MIN_BODY_TOKENS: usize = 64;
+
+fn part_header(number: usize, total: usize) -> String {
+    format!(
+        "[Part {number}/{total}] This export is split into {total} parts to fit the \
+         token limit. Read all {total} parts before answering.\n\n"
+    )
+}
+
+/// Renders the planned export and splits it into parts of at most
+/// `max_tokens` tokens each, see `split_into_parts`.
+pub fn split_export(
+    plan: &ExportPlan,
+    max_tokens: usize,
+    bpe: &CoreBPE,
+) -> Result<ExportParts, String> {
+    let mut chunks = Vec::new();
+    let (_, redactions) = file_service::write_file_content(plan, bpe, |chunk| {
+        chunks.push(chunk.to_string());
+        Ok(())
+    })?;
+    Ok(ExportParts {
+        parts: split_into_parts(&chunks, max_tokens, bpe)?,
+        redactions,
+    })
+}
+
+/// Splits a rendered export into parts of at most `max_tokens` tokens each,
+/// header included.
+///
+/// `chunks` are the export's pieces as the renderer produced them, one per
+/// file plus the opening and closing. A chunk only ever shares a part with its
+/// neighbours or gets one to itself; chunks too big for any part are cut
+/// between lines, and lines too big for a part are cut wherever they must be.
+///
+/// Token counts add up per piece, so a part can come out a handful of tokens
+/// off from encoding it in one pass.
+pub fn split_into_parts(
+    chunks: &[String],
+    max_tokens: usize,
+    bpe: &CoreBPE,
+) -> Result<Vec<ExportPart>, String> {
+    let header_tokens = bpe
+        .encode_with_special_tokens(&part_header(WIDEST_PART_NUMBER, WIDEST_PART_NUMBER))
+        .len();
+    let body_limit = max_tokens.saturating_sub(header_tokens);
+    if body_limit < MIN_BODY_TOKENS {
+        return Err(format!(
+            "A part limit of {} tokens leaves no room for content, use at least {}",
+            max_tokens,
+            header_tokens + MIN_BODY_TOKENS
+        ));
+    }
+
+    let mut parts = PartBuilder {
+        bodies: Vec::new(),
+        current: String::new(),
+        current_tokens: 0,
+        body_limit,
+    };
+    for chunk in chunks {
+        let tokens = bpe.encode_with_special_tokens(chunk).len();
+        if tokens <= body_limit {
+            // A chunk that fits in a part is never cut
+            if parts.current_tokens + tokens > body_limit {
+                parts.finish();
+            }
+            parts.push(chunk, tokens);
+            continue;
+        }
+
+        for line in chunk.split_inclusive('\n') {
+            let tokens = bpe.encode_with_special_tokens(line).len();
+            if tokens <= body_limit {
+                parts.push_fitting(line, tokens);
+            } else {
+                for piece in cut_line(line, body_limit, bpe) {
+                    let tokens = bpe.encode_with_special_tokens(&piece).len();
+                    parts.push_fitting(&piece, tokens);
+                }
+            }
+        }
+    }
+    parts.finish();
+
+    let total = parts.bodies.len();
+    Ok(parts
+        .bodies
+        .into_iter()
+        .enumerate()
+        .map(|(i, (body, body_tokens))| {
+            let header = part_header(i + 1, total);
+            ExportPart {
+                number: i + 1,
+                total,
+                token_count: bpe.encode_with_special_tokens(&header).len() + body_tokens,
+                content: header + &body,
+            }
+        })
+        .collect())
+}
+
+// Collects part bodies and their token counts
+struct PartBuilder {
+    bodies: Vec<(String, usize)>,
+    current: String,
+    current_tokens: usize,
+    body_limit: usize,
+}
+
+impl PartBuilder {
+    fn push(&mut self, text: &str, tokens: usize) {
+        self.current.push_str(text);
+        self.current_tokens += tokens;
+    }
+
+    // Starts a new part first when `text` doesn't fit in the current one
+    fn push_fitting(&mut self, text: &str, tokens: usize) {
+        if self.current_tokens + tokens > self.body_limit {
+            self.finish();
+        }
+        self.push(text, tokens);
+    }
+
+    fn finish(&mut self) {
+        if !self.current.is_empty() {
+            self.bodies.push((
+                std::mem::take(&mut self.current),
+                std::mem::take(&mut self.current_tokens),
+            ));
+        }
+    }
+}
+
+// Cuts a line into pieces of at most `limit` tokens, on char boundaries
+fn cut_line(line: &str, limit: usize, bpe: &CoreBPE) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if bpe.encode_with_special_tokens(rest).len() <= limit {
+            pieces.push(rest.to_string());
+            break;
+        }
+        // Longest prefix that still fits, found by bisecting over char boundaries
+        let boundaries: Vec<usize> = rest.char_indices().map(|(i, _)| i).skip(1).collect();
+        let (mut low, mut high) = (0, boundaries.len());
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if bpe
+                .encode_with_special_tokens(&rest[..boundaries[mid - 1]])
+                .len()
+                <= limit
+            {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        // Always make progress, even if a single char is over the limit
+        let end = boundaries
+            .get(low.max(1) - 1)
+            .copied()
+            .unwrap_or(rest.len());
+        pieces.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::token_service;
+
+    const LIMIT: usize = 150;
+
+    // Numbered lines of about `tokens` tokens in total
+    fn chunk(name: &str, tokens: usize) -> String {
+        (0..tokens / 5)
+            .map(|i| format!("{} line {}\n", name, i))
+            .collect()
+    }
+
+    fn split(chunks: &[String]) -> Vec<ExportPart> {
+        split_into_parts(chunks, LIMIT, &token_service::shared_bpe().unwrap()).unwrap()
+    }
+
+    fn bodies(parts: &[ExportPart]) -> Vec<&str> {
+        parts
+            .iter()
+            .map(|part| {
+                part.content
+                    .strip_prefix(&part_header(part.number, part.total))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parts_stay_under_the_limit_with_their_header() {
+        let bpe = token_service::shared_bpe().unwrap();
+        let chunks: Vec<String> = (0..12).map(|i| chunk(&format!("f{}", i), 40)).collect();
+        let parts = split(&chunks);
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.token_count <= LIMIT, "{}", part.token_count);
+            assert!(bpe.encode_with_special_tokens(&part.content).len() <= LIMIT);
+        }
+        assert_eq!(bodies(&parts).concat(), chunks.concat());
+    }
+
+    #[test]
+    fn parts_are_numbered_in_order() {
+        let chunks: Vec<String> = (0..12).map(|i| chunk(&format!("f{}", i), 40)).collect();
+        let parts = split(&chunks);
+        let total = parts.len();
+        for (i, part) in parts.iter().enumerate() {
+            assert_eq!((part.number, part.total), (i + 1, total));
+            assert!(part
+                .content
+                .starts_with(&format!("[Part {}/{}] ", i + 1, total)));
+        }
+    }
+
+    #[test]
+    fn only_files_over_the_limit_are_cut() {
+        let small = chunk("small", 60);
+        let fits = chunk("fits", 90);
+        let huge = chunk("huge", 400);
+        let parts = split(&[small.clone(), fits.clone(), huge.clone()]);
+        let bodies = bodies(&parts);
+
+        // `fits` doesn't fit next to `small`, so it moves to a part of its own
+        assert_eq!(bodies[0], small);
+        assert!(bodies[1].starts_with(&fits));
+        assert_eq!(bodies.concat(), [small, fits, huge.clone()].concat());
+        // `huge` is cut between lines
+        assert!(bodies[2..].iter().all(|body| body.ends_with('\n')));
+        assert!(bodies.iter().filter(|body| body.contains("huge")).count() > 1);
+    }
+
+    #[test]
+    fn lines_over_the_limit_are_cut_too() {
+        let bpe = token_service::shared_bpe().unwrap();
+        let line = "word ".repeat(1000) + "ünïcödé".repeat(100).as_str() + "\n";
+        let parts = split(std::slice::from_ref(&line));
+        assert!(parts.len() > 5);
+        for part in &parts {
+            assert!(bpe.encode_with_special_tokens(&part.content).len() <= LIMIT);
+        }
+        assert_eq!(bodies(&parts).concat(), line);
+    }
+
+    #[test]
+    fn limits_without_room_for_content_are_rejected() {
+        let error = split_into_parts(&[chunk("a", 10)], 40, &token_service::shared_bpe().unwrap())
+            .unwrap_err();
+        assert!(error.contains("leaves no room for content"), "{}", error);
+    }
+}
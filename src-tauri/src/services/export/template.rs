@@ -0,0 +1,198 @@
+use crate::domain::prompt_template::PromptTemplate;
+use std::{borrow::Cow, collections::HashSet};
+
+/// Every placeholder a template may use.
+pub const PLACEHOLDERS: &[&str] = &[
+    "file_map",
+    "file_contents",
+    "instructions",
+    "token_count",
+    "workspace_name",
+];
+
+/// Values substituted into a template.
+pub struct TemplateValues<'a> {
+    pub file_map: &'a str,
+    pub file_contents: &'a str,
+    pub instructions: &'a str,
+    /// Tokens in the whole rendered prompt, template text included.
+    pub token_count: usize,
+    pub workspace_name: &'a str,
+}
+
+impl TemplateValues<'_> {
+    fn get(&self, placeholder: &str) -> Option<Cow<'_, str>> {
+        match placeholder {
+            "file_map" => Some(Cow::Borrowed(self.file_map)),
+            "file_contents" => Some(Cow::Borrowed(self.file_contents)),
+            "instructions" => Some(Cow::Borrowed(self.instructions)),
+            "token_count" => Some(Cow::Owned(self.token_count.to_string())),
+            "workspace_name" => Some(Cow::Borrowed(self.workspace_name)),
+            _ => None,
+        }
+    }
+}
+
+/// A piece of a template body: literal text or a `{{placeholder}}`.
+pub enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+// Splits a template body into text and placeholders. Whitespace inside the
+// braces is ignored, so `{{ file_map }}` works too.
+fn parse(body: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = body;
+    while let Some(open) = rest.find("{{") {
+        let after_open = &rest[open + 2..];
+        let close = after_open
+            .find("}}")
+            .ok_or_else(|| format!("Unclosed placeholder near \"{{{{{}\"", preview(after_open)))?;
+        segments.push(Segment::Text(&rest[..open]));
+        segments.push(Segment::Placeholder(after_open[..close].trim()));
+        rest = &after_open[close + 2..];
+    }
+    segments.push(Segment::Text(rest));
+    Ok(segments)
+}
+
+fn preview(text: &str) -> String {
+    text.chars().take(20).collect()
+}
+
+/// Checks a template for unknown or unclosed placeholders.
+pub fn validate_template(template: &PromptTemplate) -> Result<(), String> {
+    if template.name.trim().is_empty() {
+        return Err("Prompt templates need a name".to_string());
+    }
+    let segments =
+        parse(&template.body).map_err(|e| format!("Template '{}': {}", template.name, e))?;
+    for segment in segments {
+        if let Segment::Placeholder(name) = segment {
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Template '{}' uses unknown placeholder {{{{{}}}}}; expected one of {}",
+                    template.name,
+                    name,
+                    PLACEHOLDERS
+                        .iter()
+                        .map(|p| format!("{{{{{}}}}}", p))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validates every template and makes sure no two share a name.
+pub fn validate_templates(templates: &[PromptTemplate]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for template in templates {
+        validate_template(template)?;
+        if !names.insert(template.name.trim()) {
+            return Err(format!(
+                "There is more than one template named '{}'",
+                template.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A template body split into text and placeholders, so it can be written
+/// out piece by piece. Templates are validated when they're saved, but
+/// anything that still doesn't parse is one piece of text.
+pub fn template_segments(body: &str) -> Vec<Segment<'_>> {
+    parse(body).unwrap_or_else(|_| vec![Segment::Text(body)])
+}
+
+/// One piece of a template with its placeholder filled in; unknown
+/// placeholders are written out as is.
+pub fn render_segment<'a>(segment: &Segment<'a>, values: &'a TemplateValues) -> Cow<'a, str> {
+    match segment {
+        Segment::Text(text) => Cow::Borrowed(text),
+        Segment::Placeholder(name) => values
+            .get(name)
+            .unwrap_or_else(|| Cow::Owned(format!("{{{{{}}}}}", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::prompt_template::default_prompt_templates;
+
+    fn template(body: &str) -> PromptTemplate {
+        PromptTemplate {
+            name: "Test".to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn render_template(body: &str, values: &TemplateValues) -> String {
+        template_segments(body)
+            .iter()
+            .map(|segment| render_segment(segment, values))
+            .collect()
+    }
+
+    fn values<'a>(file_contents: &'a str) -> TemplateValues<'a> {
+        TemplateValues {
+            file_map: "src/\n└── main.rs",
+            file_contents,
+            instructions: "Be brief.",
+            token_count: 1234,
+            workspace_name: "demo",
+        }
+    }
+
+    #[test]
+    fn every_placeholder_is_filled_in() {
+        let body =
+            "{{workspace_name}}|{{ token_count }}|{{instructions}}|{{file_map}}|{{file_contents}}";
+        assert!(validate_template(&template(body)).is_ok());
+        assert_eq!(
+            render_template(body, &values("fn main() {}")),
+            "demo|1234|Be brief.|src/\n└── main.rs|fn main() {}"
+        );
+        for placeholder in PLACEHOLDERS {
+            assert!(values("").get(placeholder).is_some(), "{}", placeholder);
+        }
+    }
+
+    #[test]
+    fn unknown_and_unclosed_placeholders_are_rejected() {
+        let error = validate_template(&template("Hi {{foo}}")).unwrap_err();
+        assert!(error.contains("unknown placeholder {{foo}}"), "{}", error);
+        let error = validate_template(&template("Hi {{file_map")).unwrap_err();
+        assert!(error.contains("Unclosed placeholder"), "{}", error);
+        // Unknown placeholders that get past validation are written out as is
+        assert_eq!(render_template("Hi {{foo}}", &values("")), "Hi {{foo}}");
+    }
+
+    #[test]
+    fn braces_in_file_contents_are_not_expanded() {
+        let contents = "const t = \"{{instructions}}\"; // {{ file_map }}";
+        assert_eq!(
+            render_template("{{file_contents}}", &values(contents)),
+            contents
+        );
+    }
+
+    #[test]
+    fn templates_need_unique_names() {
+        assert!(validate_templates(&default_prompt_templates()).is_ok());
+        let duplicated = vec![template("{{file_map}}"), template("{{file_contents}}")];
+        assert!(validate_templates(&duplicated)
+            .unwrap_err()
+            .contains("more than one template named 'Test'"));
+        assert!(validate_template(&PromptTemplate {
+            name: " ".to_string(),
+            body: String::new(),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,593 @@
+use crate::domain::content_transforms::ContentTransforms;
+
+use super::content::FileContent;
+
+/// Comment syntax of a language family, plus the string forms that can hide
+/// something that looks like a comment.
+struct CommentSyntax {
+    line: &'static str,
+    block: Option<(&'static str, &'static str)>,
+    nested_blocks: bool,
+    quotes: &'static [char],
+    /// Quotes whose strings may run over several lines.
+    multiline_quotes: &'static [char],
+    /// `"""` and `'''` strings (Python, TOML).
+    triple_quotes: bool,
+    /// Quotes whose strings have no escapes, so `\\` is just a character in
+    /// them (`'` in TOML, shell and YAML).
+    literal_quotes: &'static [char],
+    /// The line marker only counts at the start of a word (`#` in shell and YAML).
+    line_needs_boundary: bool,
+    /// `'` can open a lifetime as well as a character literal (Rust).
+    lifetimes: bool,
+    /// `r"..."` and `r#"..."#` strings, which have no escapes (Rust).
+    raw_strings: bool,
+    /// `/.../` regex literals, which can hold `//` (JavaScript).
+    regex_literals: bool,
+}
+
+const RUST: CommentSyntax = CommentSyntax {
+    line: "//",
+    block: Some(("/*", "*/")),
+    nested_blocks: true,
+    quotes: &['"'],
+    multiline_quotes: &['"'],
+    triple_quotes: false,
+    literal_quotes: &[],
+    line_needs_boundary: false,
+    lifetimes: true,
+    raw_strings: true,
+    regex_literals: false,
+};
+
+const JS_LIKE: CommentSyntax = CommentSyntax {
+    line: "//",
+    block: Some(("/*", "*/")),
+    nested_blocks: false,
+    quotes: &['"', '\'', '`'],
+    multiline_quotes: &['`'],
+    triple_quotes: false,
+    literal_quotes: &[],
+    line_needs_boundary: false,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: true,
+};
+
+const C_LIKE: CommentSyntax = CommentSyntax {
+    line: "//",
+    block: Some(("/*", "*/")),
+    nested_blocks: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+    triple_quotes: false,
+    literal_quotes: &[],
+    line_needs_boundary: false,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: false,
+};
+
+const PYTHON_LIKE: CommentSyntax = CommentSyntax {
+    line: "#",
+    block: None,
+    nested_blocks: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+    triple_quotes: true,
+    literal_quotes: &[],
+    line_needs_boundary: false,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: false,
+};
+
+const TOML: CommentSyntax = CommentSyntax {
+    line: "#",
+    block: None,
+    nested_blocks: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+    triple_quotes: true,
+    literal_quotes: &['\''],
+    line_needs_boundary: false,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: false,
+};
+
+const SHELL: CommentSyntax = CommentSyntax {
+    line: "#",
+    block: None,
+    nested_blocks: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &['"', '\''],
+    triple_quotes: false,
+    literal_quotes: &['\''],
+    line_needs_boundary: true,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: false,
+};
+
+const YAML: CommentSyntax = CommentSyntax {
+    line: "#",
+    block: None,
+    nested_blocks: false,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+    triple_quotes: false,
+    literal_quotes: &['\''],
+    line_needs_boundary: true,
+    lifetimes: false,
+    raw_strings: false,
+    regex_literals: false,
+};
+
+fn comment_syntax(extension: &str) -> Option<&'static CommentSyntax> {
+    match extension.to_ascii_lowercase().as_str() {
+        "rs" => Some(&RUST),
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => Some(&JS_LIKE),
+        // Go's raw strings use backticks just like JS template literals
+        "go" => Some(&JS_LIKE),
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Some(&C_LIKE),
+        "py" | "pyi" => Some(&PYTHON_LIKE),
+        "toml" => Some(&TOML),
+        "sh" | "bash" | "zsh" => Some(&SHELL),
+        "yaml" | "yml" => Some(&YAML),
+        _ => None,
+    }
+}
+
+/// Applies the enabled transforms to a file's content. Comment stripping is
+/// skipped for languages whose comment syntax isn't known.
+pub fn apply_transforms(
+    content: &mut FileContent,
+    transforms: &ContentTransforms,
+    extension: &str,
+) {
+    if transforms.strip_comments {
+        if let Some(syntax) = comment_syntax(extension) {
+            content.rewrite_lines(|lines| strip_comments(lines, syntax));
+        }
+    }
+    if transforms.trim_trailing_whitespace {
+        content.rewrite_lines(|lines| {
+            lines
+                .iter()
+                .map(|line| Some(line.trim_end().to_string()))
+                .collect()
+        });
+    }
+    if transforms.collapse_blank_lines {
+        content.rewrite_lines(collapse_blank_lines);
+    }
+}
+
+fn collapse_blank_lines(lines: &[&str]) -> Vec<Option<String>> {
+    let mut previous_blank = false;
+    lines
+        .iter()
+        .map(|line| {
+            let blank = line.trim().is_empty();
+            let keep = !(blank && previous_blank);
+            previous_blank = blank;
+            keep.then(|| line.to_string())
+        })
+        .collect()
+}
+
+// A string the scanner is currently inside of
+enum OpenQuote {
+    Single {
+        quote: char,
+        multiline: bool,
+    },
+    Triple(char),
+    /// A raw string closed by `"` and this many `#`.
+    Raw(usize),
+}
+
+// Removes comments from a run of lines. Lines that held nothing but comments
+// are dropped; every other line keeps its place.
+fn strip_comments(lines: &[&str], syntax: &CommentSyntax) -> Vec<Option<String>> {
+    let chars: Vec<char> = lines.join("\n").chars().collect();
+    let starts_with = |i: usize, pattern: &str| {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| chars.get(i + offset) == Some(&c))
+    };
+
+    let mut result = Vec::with_capacity(lines.len());
+    let mut current = String::new();
+    let mut had_comment = false;
+    let mut block_depth = 0;
+    let mut open_quote: Option<OpenQuote> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\n' {
+            result.push(finish_line(&mut current, had_comment));
+            // A line inside a block comment is all comment
+            had_comment = block_depth > 0;
+            if let Some(OpenQuote::Single {
+                multiline: false, ..
+            }) = open_quote
+            {
+                open_quote = None;
+            }
+            i += 1;
+            continue;
+        }
+
+        if block_depth > 0 {
+            let (open, close) = syntax.block.unwrap_or(("", ""));
+            if syntax.nested_blocks && starts_with(i, open) {
+                block_depth += 1;
+                i += open.len();
+            } else if starts_with(i, close) {
+                block_depth -= 1;
+                i += close.len();
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+
+        if let Some(quote) = &open_quote {
+            let escapes = match quote {
+                OpenQuote::Raw(_) => false,
+                OpenQuote::Single { quote: q, .. } | OpenQuote::Triple(q) => {
+                    !syntax.literal_quotes.contains(q)
+                }
+            };
+            if escapes && c == '\\' && chars.get(i + 1).is_some_and(|next| *next != '\n') {
+                current.push(c);
+                current.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            match *quote {
+                OpenQuote::Raw(hashes)
+                    if c == '"'
+                        && chars[i + 1..]
+                            .iter()
+                            .take(hashes)
+                            .filter(|next| **next == '#')
+                            .count()
+                            == hashes =>
+                {
+                    current.extend(&chars[i..=i + hashes]);
+                    open_quote = None;
+                    i += hashes + 1;
+                }
+                OpenQuote::Triple(q) if starts_with(i, &q.to_string().repeat(3)) => {
+                    current.extend([q; 3]);
+                    open_quote = None;
+                    i += 3;
+                }
+                OpenQuote::Single { quote: q, .. } if c == q => {
+                    current.push(c);
+                    open_quote = None;
+                    i += 1;
+                }
+                _ => {
+                    current.push(c);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        // Keep a shebang even though it looks like a comment
+        let is_shebang = i == 0 && syntax.line == "#" && starts_with(i, "#!");
+        let at_word_start = i == 0 || chars[i - 1].is_whitespace();
+        if !is_shebang
+            && starts_with(i, syntax.line)
+            && (!syntax.line_needs_boundary || at_word_start)
+        {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            had_comment = true;
+            continue;
+        }
+
+        if let Some((open, _)) = syntax.block {
+            if starts_with(i, open) {
+                block_depth = 1;
+                had_comment = true;
+                i += open.len();
+                continue;
+            }
+        }
+
+        if syntax.regex_literals && c == '/' && regex_can_start(&current) {
+            if let Some(end) = regex_end(&chars, i) {
+                current.extend(&chars[i..=end]);
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if syntax.triple_quotes && (starts_with(i, "\"\"\"") || starts_with(i, "'''")) {
+            current.extend([c; 3]);
+            open_quote = Some(OpenQuote::Triple(c));
+            i += 3;
+            continue;
+        }
+
+        // `r"` or `br"` at the start of a word, with any number of `#` before the quote
+        let word_start =
+            |j: usize| j == 0 || !(chars[j - 1].is_alphanumeric() || chars[j - 1] == '_');
+        if syntax.raw_strings
+            && c == 'r'
+            && (word_start(i) || (i > 0 && chars[i - 1] == 'b' && word_start(i - 1)))
+        {
+            let hashes = chars[i + 1..]
+                .iter()
+                .take_while(|next| **next == '#')
+                .count();
+            if chars.get(i + 1 + hashes) == Some(&'"') {
+                current.extend(&chars[i..i + hashes + 2]);
+                open_quote = Some(OpenQuote::Raw(hashes));
+                i += hashes + 2;
+                continue;
+            }
+        }
+
+        if syntax.lifetimes && c == '\'' {
+            // 'x' and '\n' style literals are copied whole, anything else is a lifetime
+            let literal_len = if chars.get(i + 1) == Some(&'\\') {
+                chars[i + 2..]
+                    .iter()
+                    .take(10)
+                    .position(|next| *next == '\'')
+                    .map(|end| end + 3)
+            } else if chars.get(i + 2) == Some(&'\'') {
+                Some(3)
+            } else {
+                None
+            };
+            let len = literal_len.unwrap_or(1);
+            current.extend(&chars[i..i + len]);
+            i += len;
+            continue;
+        }
+
+        if syntax.quotes.contains(&c) {
+            open_quote = Some(OpenQuote::Single {
+                quote: c,
+                multiline: syntax.multiline_quotes.contains(&c),
+            });
+        }
+        current.push(c);
+        i += 1;
+    }
+    result.push(finish_line(&mut current, had_comment));
+    result
+}
+
+// Whether a `/` after `before`, the line so far, opens a regex literal rather
+// than dividing: it follows an operator, punctuation, a keyword such as
+// `return`, or nothing
+fn regex_can_start(before: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let before = before.trim_end();
+    match before.chars().last() {
+        None => true,
+        Some(c) if is_word_char(c) => {
+            let word = before.rsplit(|c| !is_word_char(c)).next().unwrap_or("");
+            matches!(
+                word,
+                "return"
+                    | "typeof"
+                    | "instanceof"
+                    | "in"
+                    | "of"
+                    | "new"
+                    | "delete"
+                    | "void"
+                    | "throw"
+                    | "case"
+                    | "do"
+                    | "else"
+                    | "yield"
+                    | "await"
+            )
+        }
+        Some(c) => !matches!(c, ')' | ']' | '}' | '"' | '\'' | '`'),
+    }
+}
+
+// Index of the `/` that closes a regex literal opened at `start`, skipping
+// escapes and `/` inside `[...]` classes; `None` if the line ends first
+fn regex_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut in_class = false;
+    let mut i = start + 1;
+    while i < chars.len() && chars[i] != '\n' {
+        match chars[i] {
+            '\\' if chars.get(i + 1).is_some_and(|next| *next != '\n') => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// A line that only held comments is dropped, anything else is kept as is
+fn finish_line(current: &mut String, had_comment: bool) -> Option<String> {
+    let line = std::mem::take(current);
+    if had_comment && line.trim().is_empty() {
+        None
+    } else if had_comment {
+        // Don't leave the whitespace that stood before a trailing comment
+        Some(line.trim_end().to_string())
+    } else {
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(content: &str, extension: &str) -> String {
+        let mut file = FileContent::from_text(content);
+        let transforms = ContentTransforms {
+            strip_comments: true,
+            ..Default::default()
+        };
+        apply_transforms(&mut file, &transforms, extension);
+        file.render()
+    }
+
+    #[test]
+    fn rust_comments_are_stripped_but_strings_kept() {
+        let source = "/// Doc comment\n\
+                      //! Inner doc comment\n\
+                      fn main() { // trailing\n    \
+                      let url = \"http://x\"; /* block */ let n = 1;\n    \
+                      let quote = '\"'; // a char literal\n\
+                      }\n";
+        assert_eq!(
+            strip(source, "rs"),
+            "fn main() {\n    let url = \"http://x\";  let n = 1;\n    let quote = '\"';\n}\n"
+        );
+    }
+
+    #[test]
+    fn rust_nested_block_comments_end_at_the_outer_close() {
+        let source = "a /* outer /* inner */ still comment */ b\n/*\n * multi\n */\nc\n";
+        assert_eq!(strip(source, "rs"), "a  b\nc\n");
+    }
+
+    #[test]
+    fn rust_raw_strings_and_lifetimes_hide_nothing() {
+        let source = "fn f<'a>(s: &'a str) -> &'a str { s } // gone\n\
+                      let r = r#\"a \" // not a comment\"#;\n\
+                      let b = br\"/* nor this */\";\n";
+        assert_eq!(
+            strip(source, "rs"),
+            "fn f<'a>(s: &'a str) -> &'a str { s }\n\
+             let r = r#\"a \" // not a comment\"#;\n\
+             let b = br\"/* nor this */\";\n"
+        );
+    }
+
+    #[test]
+    fn js_strings_and_templates_keep_comment_markers() {
+        let source = "const a = 'it\\'s // fine'; // gone\n\
+                      const t = `line one\n// still the template\n`;\n\
+                      /** JSDoc\n * @param x\n */\n\
+                      f(\"/* not a block */\");\n";
+        assert_eq!(
+            strip(source, "ts"),
+            "const a = 'it\\'s // fine';\n\
+             const t = `line one\n// still the template\n`;\n\
+             f(\"/* not a block */\");\n"
+        );
+    }
+
+    #[test]
+    fn c_block_comments_do_not_nest() {
+        let source = "int a; /* one /* two */ int b; */\nchar c = '/'; // slash\n";
+        assert_eq!(strip(source, "c"), "int a;  int b; */\nchar c = '/';\n");
+    }
+
+    #[test]
+    fn python_hash_in_strings_and_docstrings_is_kept() {
+        let source = "#!/usr/bin/env python3\n\
+                      # comment\n\
+                      def f():\n    \
+                      \"\"\"Docstring with # inside\n    \
+                      and a second line # too\"\"\"\n    \
+                      return '#' + \"#\"  # trailing\n";
+        assert_eq!(
+            strip(source, "py"),
+            "#!/usr/bin/env python3\n\
+             def f():\n    \
+             \"\"\"Docstring with # inside\n    \
+             and a second line # too\"\"\"\n    \
+             return '#' + \"#\"\n"
+        );
+    }
+
+    #[test]
+    fn shell_hash_only_starts_a_comment_at_a_word() {
+        let source = "#!/bin/bash\n\
+                      # comment\n\
+                      echo a#b '#' \"# x\" ${#ARR[@]} # trailing\n";
+        assert_eq!(
+            strip(source, "sh"),
+            "#!/bin/bash\necho a#b '#' \"# x\" ${#ARR[@]}\n"
+        );
+    }
+
+    #[test]
+    fn yaml_comments_are_stripped_but_url_fragments_kept() {
+        let source = "# header\nurl: http://x/#frag # comment\ncolor: \"#fff\"\n";
+        assert_eq!(
+            strip(source, "yml"),
+            "url: http://x/#frag\ncolor: \"#fff\"\n"
+        );
+    }
+
+    #[test]
+    fn unknown_languages_are_left_alone() {
+        let source = "# not necessarily a comment\n";
+        assert_eq!(strip(source, "md"), source);
+    }
+
+    #[test]
+    fn blank_lines_collapse_and_whitespace_is_trimmed() {
+        let mut file = FileContent::from_text("a  \n\n\n\t\nb\t\n");
+        let transforms = ContentTransforms {
+            collapse_blank_lines: true,
+            trim_trailing_whitespace: true,
+            ..Default::default()
+        };
+        apply_transforms(&mut file, &transforms, "txt");
+        assert_eq!(file.render(), "a\n\nb\n");
+    }
+
+    #[test]
+    fn toml_literal_strings_have_no_escapes() {
+        let source = "path = 'C:\\' # the drive\n\
+                      name = \"a \\\" # b\" # quoted\n\
+                      raw = '''\nC:\\''' # multi-line\n";
+        assert_eq!(
+            strip(source, "toml"),
+            "path = 'C:\\'\n\
+             name = \"a \\\" # b\"\n\
+             raw = '''\nC:\\'''\n"
+        );
+    }
+
+    #[test]
+    fn shell_single_quotes_have_no_escapes() {
+        let source = "echo 'C:\\' # gone\n";
+        assert_eq!(strip(source, "sh"), "echo 'C:\\'\n");
+    }
+
+    #[test]
+    fn js_regex_literals_keep_their_slashes() {
+        let source = "const re = /https?:\\/\\//; // gone\n\
+                      if (/[/]\\/\\//.test(s)) return /a\\/b/g; // gone too\n\
+                      const half = total / 2; // halved\n\
+                      const ratio = (a) / (b) / 2;\n";
+        assert_eq!(
+            strip(source, "js"),
+            "const re = /https?:\\/\\//;\n\
+             if (/[/]\\/\\//.test(s)) return /a\\/b/g;\n\
+             const half = total / 2;\n\
+             const ratio = (a) / (b) / 2;\n"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::domain::diff_options::{DiffBase, DiffOptions};
+use git2::{Diff, Patch, Repository, Tree};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// Opens the repository the workspace at `dir_path` belongs to, which may be
+/// the workspace root itself or one of its parents.
+pub fn open_repository(dir_path: &str) -> Result<Repository, String> {
+    Repository::discover(dir_path)
+        .map_err(|e| format!("No git repository found at {}: {}", dir_path, e))
+}
+
+/// Unified diffs of the given files, keyed by the paths as passed in. Files
+/// without changes, and files outside the repository, are left out.
+pub fn diff_files(
+    dir_path: &str,
+    file_paths: &[String],
+    options: &DiffOptions,
+) -> Result<HashMap<String, String>, String> {
+    let repo = open_repository(dir_path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Cannot diff a bare repository".to_string())?;
+    let workdir = fs::canonicalize(workdir)
+        .map_err(|e| format!("Failed to resolve repository root: {}", e))?;
+
+    // git speaks in paths relative to the working tree
+    let mut by_repo_path: HashMap<PathBuf, &String> = HashMap::new();
+    for file_path in file_paths {
+        match resolve_path(Path::new(file_path))
+            .and_then(|path| path.strip_prefix(&workdir).ok().map(Path::to_path_buf))
+        {
+            Some(repo_path) => {
+                by_repo_path.insert(repo_path, file_path);
+            }
+            None => debug!("{} is outside the repository, not diffing it", file_path),
+        }
+    }
+    if by_repo_path.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut git_options = git2::DiffOptions::new();
+    git_options
+        .context_lines(options.context_lines)
+        .disable_pathspec_match(true)
+        // New files show up as all-added instead of missing
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    for repo_path in by_repo_path.keys() {
+        git_options.pathspec(repo_path);
+    }
+    let diff = build_diff(&repo, &options.base, &mut git_options)?;
+
+    let mut diffs = HashMap::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(repo_path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        let Some(file_path) = by_repo_path.get(repo_path) else {
+            continue;
+        };
+
+        // libgit2 has no patch for binary files
+        let text = match Patch::from_diff(&diff, index)
+            .map_err(|e| format!("Failed to diff {}: {}", file_path, e))?
+        {
+            Some(mut patch) => {
+                let buf = patch
+                    .to_buf()
+                    .map_err(|e| format!("Failed to diff {}: {}", file_path, e))?;
+                String::from_utf8_lossy(&buf).into_owned()
+            }
+            None => format!(
+                "Binary files a/{0} and b/{0} differ\n",
+                repo_path.to_string_lossy()
+            ),
+        };
+        diffs.insert((*file_path).clone(), text);
+    }
+    Ok(diffs)
+}
+
+// Like `fs::canonicalize`, but also for files that no longer exist, such as
+// deleted ones in a diff: the nearest existing ancestor is resolved instead
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(current) {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |path, name| path.join(name)),
+            );
+        }
+        missing.push(current.file_name()?);
+        current = current.parent()?;
+    }
+}
+
+fn build_diff<'r>(
+    repo: &'r Repository,
+    base: &DiffBase,
+    options: &mut git2::DiffOptions,
+) -> Result<Diff<'r>, String> {
+    let diff = match base {
+        DiffBase::Head => {
+            repo.diff_tree_to_workdir_with_index(head_tree(repo).as_ref(), Some(options))
+        }
+        DiffBase::Ref { name } => {
+            let tree = repo
+                .revparse_single(name)
+                .and_then(|object| object.peel_to_tree())
+                .map_err(|e| format!("Unknown ref {}: {}", name, e))?;
+            repo.diff_tree_to_workdir_with_index(Some(&tree), Some(options))
+        }
+        DiffBase::Staged => repo.diff_tree_to_index(head_tree(repo).as_ref(), None, Some(options)),
+    };
+    diff.map_err(|e| format!("Failed to diff against {:?}: {}", base, e))
+}
+
+// `None` before the first commit, where everything counts as added
+fn head_tree(repo: &Repository) -> Option<Tree<'_>> {
+    repo.head().ok()?.peel_to_tree().ok()
+}
+
+/// Where the workspace's repository stands, for the export metadata header.
+pub struct Revision {
+    /// `None` when HEAD is detached.
+    pub branch: Option<String>,
+    pub commit: String,
+    /// Whether tracked files have changes that aren't committed.
+    pub dirty: bool,
+}
+
+/// The checked-out branch and commit of the repository the workspace at
+/// `dir_path` belongs to. Fails outside a repository and before the first commit.
+pub fn current_revision(dir_path: &str) -> Result<Revision, String> {
+    let repo = open_repository(dir_path)?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+    let commit = head
+        .peel_to_commit()
+        .map_err(|e| format!("HEAD does not point at a commit: {}", e))?;
+    let branch = if head.is_branch() {
+        head.shorthand().map(str::to_string)
+    } else {
+        None
+    };
+
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    let dirty = !repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to read repository status: {}", e))?
+        .is_empty();
+
+    Ok(Revision {
+        branch,
+        commit: commit.id().to_string(),
+        dirty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+
+    // Two commits, then in the working tree: an unstaged edit to a.txt, a staged
+    // edit to b.txt and a deleted gone.txt
+    fn fixture(name: &str) -> (PathBuf, Vec<String>) {
+        let root = std::env::temp_dir().join(format!("treesnap-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let repo = Repository::init(&root).unwrap();
+        let lines: Vec<String> = (1..=10).map(|i| i.to_string()).collect();
+        fs::write(root.join("a.txt"), lines.join("\n") + "\n").unwrap();
+        fs::write(root.join("b.txt"), "before\n").unwrap();
+        fs::write(root.join("gone.txt"), "gone\n").unwrap();
+        commit_all(&repo, "first");
+
+        let mut lines = lines;
+        lines[1] = "two".to_string();
+        fs::write(root.join("a.txt"), lines.join("\n") + "\n").unwrap();
+        commit_all(&repo, "second");
+
+        lines[8] = "nine".to_string();
+        fs::write(root.join("a.txt"), lines.join("\n") + "\n").unwrap();
+        fs::write(root.join("b.txt"), "after\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("b.txt")).unwrap();
+        index.write().unwrap();
+        fs::remove_file(root.join("gone.txt")).unwrap();
+
+        let paths = ["a.txt", "b.txt", "gone.txt"]
+            .iter()
+            .map(|name| root.join(name).to_string_lossy().into_owned())
+            .collect();
+        (root, paths)
+    }
+
+    fn diff(name: &str, base: DiffBase, context_lines: u32) -> HashMap<String, String> {
+        let (root, paths) = fixture(name);
+        let options = DiffOptions {
+            base,
+            context_lines,
+        };
+        let diffs = diff_files(&root.to_string_lossy(), &paths, &options).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        // Keyed by file name to keep the assertions short
+        diffs
+            .into_iter()
+            .map(|(path, diff)| {
+                let name = Path::new(&path).file_name().unwrap().to_string_lossy();
+                (name.into_owned(), diff)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn head_diff_covers_unstaged_staged_and_deleted_files() {
+        let diffs = diff("diff-head", DiffBase::Head, 0);
+
+        let a = &diffs["a.txt"];
+        // No context lines: the hunk is just the changed line
+        assert!(a.contains("@@ -9 +9 @@"), "{}", a);
+        assert!(a.ends_with("\n-9\n+nine\n"), "{}", a);
+        // Committed changes aren't part of it
+        assert!(!a.contains("+two"));
+        assert!(diffs["b.txt"].contains("-before\n+after\n"));
+        let gone = &diffs["gone.txt"];
+        assert!(gone.contains("deleted file mode"), "{}", gone);
+        assert!(gone.contains("-gone\n"));
+    }
+
+    #[test]
+    fn ref_diff_includes_committed_changes_with_the_chosen_context() {
+        let diffs = diff(
+            "diff-ref",
+            DiffBase::Ref {
+                name: "HEAD~1".to_string(),
+            },
+            1,
+        );
+
+        let a = &diffs["a.txt"];
+        assert!(a.contains(" 1\n-2\n+two\n 3\n"), "{}", a);
+        assert!(a.contains(" 8\n-9\n+nine\n 10\n"), "{}", a);
+        assert_eq!(diffs.len(), 3);
+    }
+
+    #[test]
+    fn staged_diff_leaves_out_the_working_tree() {
+        let diffs = diff("diff-staged", DiffBase::Staged, 3);
+
+        assert_eq!(diffs.keys().collect::<Vec<_>>(), ["b.txt"]);
+        assert!(diffs["b.txt"].contains("-before\n+after\n"));
+    }
+}
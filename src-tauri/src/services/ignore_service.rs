@@ -0,0 +1,487 @@
+use crate::domain::ignore_file_error::IgnoreFileError;
+use git2::Repository;
+use ignore::{
+    gitignore::{self, Gitignore, GitignoreBuilder},
+    WalkBuilder,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Ignore files just for TreeSnap, in gitignore syntax. They win over both
+/// `.gitignore` and the ignore patterns from the settings, so `!dist/` brings
+/// back a directory either one hides.
+pub const TREESNAPIGNORE: &str = ".treesnapignore";
+
+/// Whether `path` is one of the files whose edits change what's ignored.
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == ".gitignore" || name == TREESNAPIGNORE)
+}
+
+/// The ignore patterns from the settings, for files that don't belong in an
+/// export such as `node_modules/` or images, rooted at `dir`.
+pub fn ignore_patterns(dir: &Path, patterns: &[String]) -> Result<Gitignore, String> {
+    let mut builder = GitignoreBuilder::new(dir);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern.trim())
+            .map_err(|e| format!("Invalid ignore pattern '{}': {}", pattern, e))?;
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// A walk over the workspace that skips what `ignore` ignores, without
+/// descending into ignored directories. Hidden files are kept.
+pub fn workspace_walker(dir: &Path, ignore: Arc<WorkspaceIgnore>) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(dir);
+    builder.standard_filters(false).filter_entry(move |entry| {
+        // The walk starts with the directory itself
+        entry.depth() == 0
+            || !ignore.matches(entry.path(), entry.file_type().is_some_and(|t| t.is_dir()))
+    });
+    builder
+}
+
+/// What's left out of a workspace's file tree, watcher events and exports:
+/// - `.treesnapignore` files at every level of the workspace
+/// - the ignore patterns from the settings
+/// - what git ignores: `.gitignore` files at every level and in the
+///   directories above up to the repository's root, the repository's
+///   `info/exclude` and the global `core.excludesFile`
+///
+/// Earlier sources win, and among `.treesnapignore` and `.gitignore` files the
+/// deepest one with a matching pattern decides. `.gitignore` files count even
+/// outside a repository. Ignore files are read the first time a path in their
+/// directory is checked.
+pub struct WorkspaceIgnore {
+    root: PathBuf,
+    // Highest directory whose ignore files apply, the repository's root when
+    // the workspace is in one
+    top: PathBuf,
+    patterns: Gitignore,
+    // `.git/info/exclude`, then the global excludes file
+    excludes: Vec<Gitignore>,
+    // Read far more often than written, by every thread of a parallel walk
+    dirs: RwLock<HashMap<PathBuf, Arc<DirIgnores>>>,
+    // Ignore files read so far with lines that didn't parse
+    errors: Mutex<Vec<IgnoreFileError>>,
+}
+
+// The ignore files of a single directory, empty when it has none
+struct DirIgnores {
+    treesnapignore: Gitignore,
+    gitignore: Gitignore,
+}
+
+impl WorkspaceIgnore {
+    pub fn new(dir: &Path, patterns: &[String]) -> Result<Self, String> {
+        Self::with_global_excludes(dir, patterns, gitignore::gitconfig_excludes_path())
+    }
+
+    fn with_global_excludes(
+        dir: &Path,
+        patterns: &[String],
+        global_excludes: Option<PathBuf>,
+    ) -> Result<Self, String> {
+        let repo = Repository::discover(dir).ok();
+        let repo_root = repo.as_ref().and_then(|repo| repository_root(dir, repo));
+        // Git reads both relative to the repository's root
+        let top = repo_root.unwrap_or(dir);
+        let mut errors = Vec::new();
+        let excludes = [
+            // `.git` is a file in worktrees and submodules, the common dir is
+            // where git keeps `info/exclude` for all of them
+            repo.as_ref()
+                .filter(|_| repo_root.is_some())
+                .map(|repo| repo.commondir().join("info").join("exclude")),
+            global_excludes,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let mut builder = GitignoreBuilder::new(top);
+            if let Some(e) = builder.add(&path) {
+                errors.push(parse_error(&path, e));
+            }
+            builder.build().map_err(|e| e.to_string())
+        })
+        .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            root: dir.to_path_buf(),
+            top: top.to_path_buf(),
+            patterns: ignore_patterns(dir, patterns)?,
+            excludes,
+            dirs: RwLock::new(HashMap::new()),
+            errors: Mutex::new(errors),
+        })
+    }
+
+    /// Ignore files read so far that have lines which couldn't be parsed, by
+    /// path.
+    pub fn errors(&self) -> Vec<IgnoreFileError> {
+        let mut errors = self.errors.lock().unwrap().clone();
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        errors
+    }
+
+    /// Whether `path` is ignored itself or sits in an ignored directory.
+    /// Paths outside the workspace never are.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let components: Vec<_> = relative.components().collect();
+        let mut current = self.root.clone();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let last = i + 1 == components.len();
+            if self.matches(&current, is_dir || !last) {
+                return true;
+            }
+        }
+        false
+    }
+
+    // Checks a single path in the workspace, taking for granted that the
+    // directory it's in isn't ignored
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        // Deepest directory first
+        let dirs: Vec<Arc<DirIgnores>> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.top))
+            .map(|dir| self.dir_ignores(dir))
+            .collect();
+        let decide = |matchers: &mut dyn Iterator<Item = &Gitignore>| {
+            matchers
+                .map(|matcher| matcher.matched(path, is_dir))
+                .find(|matched| !matched.is_none())
+                .map(|matched| matched.is_ignore())
+        };
+
+        if let Some(ignored) = decide(&mut dirs.iter().map(|dir| &dir.treesnapignore)) {
+            return ignored;
+        }
+        if self.patterns.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+        decide(&mut dirs.iter().map(|dir| &dir.gitignore).chain(&self.excludes)).unwrap_or(false)
+    }
+
+    fn dir_ignores(&self, dir: &Path) -> Arc<DirIgnores> {
+        if let Some(ignores) = self.dirs.read().unwrap().get(dir) {
+            return Arc::clone(ignores);
+        }
+        let mut dirs = self.dirs.write().unwrap();
+        let ignores = dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            Arc::new(DirIgnores {
+                treesnapignore: self.read_ignore_file(&dir.join(TREESNAPIGNORE)),
+                gitignore: self.read_ignore_file(&dir.join(".gitignore")),
+            })
+        });
+        Arc::clone(ignores)
+    }
+
+    fn read_ignore_file(&self, path: &Path) -> Gitignore {
+        if !path.is_file() {
+            return Gitignore::empty();
+        }
+        let (matcher, error) = Gitignore::new(path);
+        if let Some(e) = error {
+            self.errors.lock().unwrap().push(parse_error(path, e));
+        }
+        matcher
+    }
+}
+
+// The repository's working tree as an ancestor of `dir`. git2 resolves
+// symlinks on the way, so the paths are compared resolved too.
+fn repository_root<'d>(dir: &'d Path, repo: &Repository) -> Option<&'d Path> {
+    let workdir = fs::canonicalize(repo.workdir()?).ok()?;
+    dir.ancestors()
+        .find(|ancestor| fs::canonicalize(ancestor).is_ok_and(|path| path == workdir))
+}
+
+fn parse_error(path: &Path, error: ignore::Error) -> IgnoreFileError {
+    eprintln!("Warning: Failed to parse {}: {}", path.display(), error);
+    IgnoreFileError {
+        path: path.to_string_lossy().to_string(),
+        message: error.to_string(),
+    }
+}
+
+/// Every ignore file in the workspace at `dir` with lines that couldn't be
+/// parsed, so they can be shown instead of silently matching less.
+pub fn ignore_file_errors(dir: &Path, patterns: &[String]) -> Result<Vec<IgnoreFileError>, String> {
+    let ignore = Arc::new(WorkspaceIgnore::new(dir, patterns)?);
+    // Walking reads the ignore files of every directory that isn't ignored
+    for _ in workspace_walker(dir, Arc::clone(&ignore)).build() {}
+    Ok(ignore.errors())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        application_settings::default_ignore_patterns, file_tree_node::FileTreeNode,
+    };
+    use crate::services::tree_service::build_tree_sync;
+    use git2::Signature;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("treesnap-ignore-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn ignored(ignore: &WorkspaceIgnore, root: &Path, paths: &[&str]) -> Vec<bool> {
+        paths
+            .iter()
+            .map(|path| ignore.is_ignored(&root.join(path), path.ends_with('/')))
+            .collect()
+    }
+
+    #[test]
+    fn deeper_gitignores_win() {
+        let root = temp_dir("nested");
+        write(&root.join(".gitignore"), "*.log\nbuild/\n");
+        write(&root.join("sub/.gitignore"), "!keep.log\n");
+        write(&root.join("sub/deeper/.gitignore"), "keep.log\n");
+
+        let ignore = WorkspaceIgnore::with_global_excludes(&root, &[], None).unwrap();
+        let results = ignored(
+            &ignore,
+            &root,
+            &[
+                "a.log",
+                "sub/keep.log",
+                "sub/other.log",
+                "sub/deeper/keep.log",
+                "sub/build/x.rs",
+                "main.rs",
+            ],
+        );
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [true, false, true, true, true, false]);
+    }
+
+    #[test]
+    fn repository_excludes_apply_in_subdirectories() {
+        let root = temp_dir("exclude");
+        Repository::init(&root).unwrap();
+        write(&root.join(".git/info/exclude"), "secret.txt\n");
+        write(&root.join(".gitignore"), "*.tmp\n");
+        fs::create_dir_all(root.join("app")).unwrap();
+
+        // The workspace is a directory inside the repository
+        let ignore = WorkspaceIgnore::with_global_excludes(&root.join("app"), &[], None).unwrap();
+        let results = ignored(
+            &ignore,
+            &root.join("app"),
+            &["secret.txt", "x.tmp", "main.rs"],
+        );
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [true, true, false]);
+    }
+
+    #[test]
+    fn repository_excludes_apply_in_worktrees() {
+        let main = temp_dir("worktree-main");
+        let linked = temp_dir("worktree-linked");
+        fs::remove_dir(&linked).unwrap();
+        let repo = Repository::init(&main).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])
+            .unwrap();
+        repo.worktree("linked", &linked, None).unwrap();
+        write(&main.join(".git/info/exclude"), "secret.txt\n");
+
+        // `.git` in a worktree is a file pointing back at the main repository
+        assert!(linked.join(".git").is_file());
+        let ignore = WorkspaceIgnore::with_global_excludes(&linked, &[], None).unwrap();
+        let results = ignored(&ignore, &linked, &["secret.txt", "main.rs"]);
+        fs::remove_dir_all(&main).unwrap();
+        fs::remove_dir_all(&linked).unwrap();
+        assert_eq!(results, [true, false]);
+    }
+
+    #[test]
+    fn global_excludes_come_last() {
+        let root = temp_dir("global");
+        let excludes = root.join("global-excludes");
+        write(&excludes, "*.bak\n*.swp\n");
+        write(&root.join(".gitignore"), "!keep.bak\n");
+        write(&root.join(TREESNAPIGNORE), "notes.swp\n");
+
+        let ignore = WorkspaceIgnore::with_global_excludes(&root, &[], Some(excludes)).unwrap();
+        let results = ignored(
+            &ignore,
+            &root,
+            &["old.bak", "keep.bak", "notes.swp", "main.rs"],
+        );
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [true, false, true, false]);
+    }
+
+    #[test]
+    fn treesnapignore_brings_back_what_the_defaults_hide() {
+        let root = temp_dir("reinclude");
+        write(&root.join(TREESNAPIGNORE), "!dist/\n");
+
+        let ignore =
+            WorkspaceIgnore::with_global_excludes(&root, &default_ignore_patterns(), None).unwrap();
+        let results = ignored(&ignore, &root, &["dist/app.js", "node_modules/x.js"]);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [false, true]);
+    }
+
+    #[test]
+    fn treesnapignore_beats_gitignore() {
+        let root = temp_dir("over-git");
+        write(&root.join(".gitignore"), "*.generated.ts\n");
+        write(&root.join(TREESNAPIGNORE), "!api.generated.ts\n");
+
+        let ignore = WorkspaceIgnore::with_global_excludes(&root, &[], None).unwrap();
+        let results = ignored(&ignore, &root, &["api.generated.ts", "db.generated.ts"]);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [false, true]);
+    }
+
+    #[test]
+    fn nested_treesnapignore_beats_the_root_one() {
+        let root = temp_dir("nested-treesnap");
+        write(&root.join(TREESNAPIGNORE), "*.md\n");
+        write(&root.join("docs").join(TREESNAPIGNORE), "!guide.md\n");
+
+        let ignore = WorkspaceIgnore::with_global_excludes(&root, &[], None).unwrap();
+        let results = ignored(
+            &ignore,
+            &root,
+            &["docs/guide.md", "docs/other.md", "README.md"],
+        );
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(results, [false, true, true]);
+    }
+
+    // The tree is built by walking, the watcher and exports check single paths
+    // with `is_ignored`; both must leave out the same files
+    #[test]
+    fn tree_and_path_checks_agree() {
+        let root = temp_dir("agree");
+        write(&root.join(".gitignore"), "build/\n*.generated.ts\n");
+        write(
+            &root.join(TREESNAPIGNORE),
+            "!dist/\n!api.generated.ts\n*.md\n",
+        );
+        write(&root.join("docs").join(TREESNAPIGNORE), "!guide.md\n");
+        for file in [
+            "src/main.ts",
+            "src/api.generated.ts",
+            "src/db.generated.ts",
+            "dist/app.js",
+            "build/out.js",
+            "node_modules/lib/index.js",
+            "docs/guide.md",
+            "docs/other.md",
+            "README.md",
+        ] {
+            write(&root.join(file), "x\n");
+        }
+
+        let patterns = default_ignore_patterns();
+        let mut in_tree = Vec::new();
+        collect_files(
+            &build_tree_sync(&root, &root, &patterns, &HashMap::new(), None).unwrap(),
+            &mut in_tree,
+        );
+        let ignore = WorkspaceIgnore::new(&root, &patterns).unwrap();
+        let mut on_disk = Vec::new();
+        collect_disk_files(&root, &mut on_disk);
+        let mut not_ignored: Vec<PathBuf> = on_disk
+            .into_iter()
+            .filter(|path| !ignore.is_ignored(path, false))
+            .collect();
+        fs::remove_dir_all(&root).unwrap();
+
+        in_tree.sort();
+        not_ignored.sort();
+        assert_eq!(in_tree, not_ignored);
+        let names: Vec<String> = in_tree
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ".gitignore",
+                TREESNAPIGNORE,
+                "dist/app.js",
+                "docs/.treesnapignore",
+                "docs/guide.md",
+                "src/api.generated.ts",
+                "src/main.ts",
+            ]
+        );
+    }
+
+    fn collect_files(nodes: &[FileTreeNode], out: &mut Vec<PathBuf>) {
+        for node in nodes {
+            match &node.children {
+                Some(children) => collect_files(children, out),
+                None if !node.is_directory => out.push(PathBuf::from(&node.path)),
+                None => {}
+            }
+        }
+    }
+
+    fn collect_disk_files(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_disk_files(&path, out);
+            } else {
+                out.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_errors_are_reported_and_the_rest_still_applies() {
+        let root = temp_dir("parse-error");
+        write(&root.join("src").join(TREESNAPIGNORE), "*.log\nsrc/[\n");
+
+        let errors = ignore_file_errors(&root, &[]).unwrap();
+        let ignore = WorkspaceIgnore::new(&root, &[]).unwrap();
+        let log_ignored = ignore.is_ignored(&root.join("src/debug.log"), false);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].path,
+            root.join("src").join(TREESNAPIGNORE).to_string_lossy()
+        );
+        assert!(errors[0].message.contains("src/["), "{}", errors[0].message);
+        assert!(log_ignored);
+    }
+}
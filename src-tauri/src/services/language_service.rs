@@ -0,0 +1,292 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+// Files whose name alone says what they are
+const FILENAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "dockerfile"),
+    ("Containerfile", "dockerfile"),
+    ("Makefile", "makefile"),
+    ("GNUmakefile", "makefile"),
+    ("makefile", "makefile"),
+    ("CMakeLists.txt", "cmake"),
+    ("Jenkinsfile", "groovy"),
+    ("Rakefile", "ruby"),
+    ("Gemfile", "ruby"),
+    ("Podfile", "ruby"),
+    ("Vagrantfile", "ruby"),
+    ("Brewfile", "ruby"),
+    ("Procfile", "yaml"),
+    ("Cargo.lock", "toml"),
+    ("go.mod", "go"),
+    ("go.sum", "text"),
+    (".bashrc", "bash"),
+    (".bash_profile", "bash"),
+    (".bash_aliases", "bash"),
+    (".profile", "bash"),
+    (".zshrc", "zsh"),
+    (".zprofile", "zsh"),
+    (".gitignore", "gitignore"),
+    (".gitattributes", "gitattributes"),
+    (".dockerignore", "gitignore"),
+    (".editorconfig", "ini"),
+    (".npmrc", "ini"),
+];
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("tsx", "tsx"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("rb", "ruby"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hh", "cpp"),
+    ("hpp", "cpp"),
+    ("hxx", "cpp"),
+    ("cs", "csharp"),
+    ("fs", "fsharp"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("pm", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("ml", "ocaml"),
+    ("clj", "clojure"),
+    ("zig", "zig"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("ps1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sass", "sass"),
+    ("less", "less"),
+    ("json", "json"),
+    ("jsonc", "jsonc"),
+    ("json5", "json5"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("ini", "ini"),
+    ("cfg", "ini"),
+    ("xml", "xml"),
+    ("svg", "xml"),
+    ("md", "markdown"),
+    ("mdx", "mdx"),
+    ("rst", "rst"),
+    ("tex", "latex"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("proto", "protobuf"),
+    ("tf", "hcl"),
+    ("hcl", "hcl"),
+    ("nix", "nix"),
+    ("dockerfile", "dockerfile"),
+    ("mk", "makefile"),
+    ("cmake", "cmake"),
+    ("gradle", "groovy"),
+    ("groovy", "groovy"),
+    ("diff", "diff"),
+    ("patch", "diff"),
+    ("txt", "text"),
+];
+
+// Interpreters named on a `#!` line, without any version suffix
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("bash", "bash"),
+    ("sh", "bash"),
+    ("dash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("python", "python"),
+    ("node", "javascript"),
+    ("deno", "typescript"),
+    ("bun", "typescript"),
+    ("ts-node", "typescript"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("Rscript", "r"),
+    ("pwsh", "powershell"),
+];
+
+fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, language)| *language)
+}
+
+/// Language name of a file for code fences and the file tree, such as `rust`
+/// for `main.rs` or `dockerfile` for `Dockerfile`.
+///
+/// `overrides` from the settings win over everything else; their keys are
+/// either an exact file name (`Jenkinsfile`) or an extension pattern (`*.h`).
+/// After them come the built-in file names, extensions, and finally the
+/// interpreter on the `first_line` when it's a shebang.
+pub fn detect_language(
+    path: &Path,
+    first_line: Option<&str>,
+    overrides: &HashMap<String, String>,
+) -> Option<String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let extension = path.extension().and_then(|e| e.to_str());
+
+    if let Some(language) = overrides
+        .get(file_name)
+        .or_else(|| extension.and_then(|extension| overrides.get(&format!("*.{}", extension))))
+    {
+        return Some(language.clone());
+    }
+
+    lookup(FILENAMES, file_name)
+        .or_else(|| lookup(EXTENSIONS, &extension?.to_ascii_lowercase()))
+        .or_else(|| first_line.and_then(shebang_language))
+        .map(str::to_string)
+}
+
+/// Like `detect_language`, reading the first line from disk only when the
+/// name doesn't settle it.
+pub fn detect_file_language(path: &Path, overrides: &HashMap<String, String>) -> Option<String> {
+    detect_language(path, None, overrides).or_else(|| {
+        let first_line = read_first_line(path)?;
+        detect_language(path, Some(&first_line), overrides)
+    })
+}
+
+// `#!/usr/bin/env python3` and `#!/bin/bash -e` both name their interpreter
+fn shebang_language(line: &str) -> Option<&'static str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = Path::new(words.next()?).file_name()?.to_str()?;
+    if program == "env" {
+        // Skip flags such as `env -S`
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    lookup(INTERPRETERS, name)
+}
+
+fn read_first_line(path: &Path) -> Option<String> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    // A shebang is short, don't read a whole minified file looking for one
+    let mut buf = Vec::new();
+    reader.by_ref().take(256).read_until(b'\n', &mut buf).ok()?;
+    buf.starts_with(b"#!")
+        .then(|| String::from_utf8_lossy(&buf).trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(path: &str, first_line: Option<&str>) -> Option<String> {
+        detect_language(Path::new(path), first_line, &HashMap::new())
+    }
+
+    #[test]
+    fn extensions() {
+        assert_eq!(detect("src/main.rs", None).as_deref(), Some("rust"));
+        assert_eq!(detect("App.ts", None).as_deref(), Some("typescript"));
+        // Fences keep JSX flavours apart, highlighters know `tsx` and `jsx`
+        assert_eq!(detect("App.tsx", None).as_deref(), Some("tsx"));
+        assert_eq!(detect("README.MD", None).as_deref(), Some("markdown"));
+        assert_eq!(detect("data.unknown", None), None);
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(detect("Dockerfile", None).as_deref(), Some("dockerfile"));
+        assert_eq!(detect("app/Makefile", None).as_deref(), Some("makefile"));
+        assert_eq!(detect("/home/me/.bashrc", None).as_deref(), Some("bash"));
+        // The file name wins over its extension
+        assert_eq!(detect("CMakeLists.txt", None).as_deref(), Some("cmake"));
+    }
+
+    #[test]
+    fn shebangs() {
+        assert_eq!(
+            detect("bin/tool", Some("#!/usr/bin/env python3")).as_deref(),
+            Some("python")
+        );
+        assert_eq!(
+            detect("run", Some("#!/bin/bash -e")).as_deref(),
+            Some("bash")
+        );
+        assert_eq!(
+            detect("run", Some("#!/usr/bin/env -S deno run")).as_deref(),
+            Some("typescript")
+        );
+        assert_eq!(detect("run", Some("#!/usr/bin/unknown")), None);
+        assert_eq!(detect("run", Some("python3")), None);
+        // Extensions come first
+        assert_eq!(
+            detect("build.rs", Some("#!/usr/bin/env python3")).as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn shebangs_are_read_from_disk() {
+        let dir = std::env::temp_dir().join(format!("treesnap-language-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env python3\nprint('hi')\n").unwrap();
+        let plain = dir.join("NOTES");
+        std::fs::write(&plain, "just text\n").unwrap();
+
+        let language = detect_file_language(&script, &HashMap::new());
+        let no_language = detect_file_language(&plain, &HashMap::new());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(language.as_deref(), Some("python"));
+        assert_eq!(no_language, None);
+    }
+
+    #[test]
+    fn overrides_beat_the_built_in_tables() {
+        let overrides: HashMap<String, String> = [
+            ("*.h".to_string(), "cpp".to_string()),
+            ("Dockerfile".to_string(), "docker".to_string()),
+            ("*.tsx".to_string(), "typescript".to_string()),
+        ]
+        .into();
+        let detect = |path: &str| detect_language(Path::new(path), None, &overrides);
+        assert_eq!(detect("include/vec.h").as_deref(), Some("cpp"));
+        assert_eq!(detect("Dockerfile").as_deref(), Some("docker"));
+        assert_eq!(detect("App.tsx").as_deref(), Some("typescript"));
+        assert_eq!(detect("vec.c").as_deref(), Some("c"));
+    }
+}
@@ -1,5 +1,10 @@
 pub mod cache_service;
+pub mod encoding_service;
+pub mod export;
 pub mod file_service;
+pub mod git_service;
+pub mod ignore_service;
+pub mod language_service;
 pub mod license;
 pub mod settings_service;
 pub mod token_service;
@@ -1,5 +1,10 @@
 use crate::{
-    constants::SETTINGS_STORE_FILENAME, domain::application_settings::ApplicationSettings,
+    constants::SETTINGS_STORE_FILENAME,
+    domain::{
+        application_settings::{default_ignore_patterns, ApplicationSettings, SCHEMA_VERSION},
+        prompt_template::{default_prompt_templates, PromptTemplate},
+    },
+    services::{export, ignore_service},
 };
 use serde_json;
 use std::path::{Path, PathBuf};
@@ -8,6 +13,7 @@ use tauri_plugin_store::StoreExt;
 use tracing::{debug, error, info, warn};
 
 const SETTINGS_KEY: &str = "application_settings";
+const PROMPT_TEMPLATES_KEY: &str = "prompt_templates";
 
 pub fn load_application_settings_internal(app_handle: &AppHandle) -> ApplicationSettings {
     info!("Attempting to load application settings using StoreExt...");
@@ -27,8 +33,15 @@ pub fn load_application_settings_internal(app_handle: &AppHandle) -> Application
     match store.get(SETTINGS_KEY) {
         Some(settings_value) => {
             match serde_json::from_value::<ApplicationSettings>(settings_value.clone()) {
-                Ok(loaded_settings) => {
+                Ok(mut loaded_settings) => {
                     info!("Successfully loaded settings: {:?}", loaded_settings);
+                    if loaded_settings.schema_version < SCHEMA_VERSION {
+                        info!(
+                            "Migrating settings from schema version {} to {}",
+                            loaded_settings.schema_version, SCHEMA_VERSION
+                        );
+                        migrate(&mut loaded_settings);
+                    }
                     loaded_settings
                 }
                 Err(e) => {
@@ -47,11 +60,26 @@ pub fn load_application_settings_internal(app_handle: &AppHandle) -> Application
     }
 }
 
+// Brings settings saved with an older schema up to date; fields added since
+// were already filled in with their defaults when they were deserialized
+fn migrate(settings: &mut ApplicationSettings) {
+    if settings.schema_version < 2 {
+        // The ignore patterns used to be built in
+        settings.ignore_patterns = default_ignore_patterns();
+    }
+    settings.schema_version = SCHEMA_VERSION;
+}
+
 pub fn save_application_settings_internal(
     app_handle: &AppHandle,
     settings: &ApplicationSettings,
 ) -> Result<(), String> {
     info!("Attempting to save application settings: {:?}", settings);
+    // Reject custom redaction rules that don't compile before they break every export
+    export::Redactor::new(&settings.redaction)?;
+    // Same for ignore patterns, which every file tree is built with
+    ignore_service::ignore_patterns(Path::new(""), &settings.ignore_patterns)?;
+
     let store = app_handle
         .store(Path::new(SETTINGS_STORE_FILENAME))
         .map_err(|e| format!("Cannot open settings store: {}", e))?;
@@ -65,3 +93,84 @@ pub fn save_application_settings_internal(
         .save()
         .map_err(|e| format!("Failed to write settings to disk: {}", e))
 }
+
+pub fn load_prompt_templates_internal(app_handle: &AppHandle) -> Vec<PromptTemplate> {
+    let store = match app_handle.store(Path::new(SETTINGS_STORE_FILENAME)) {
+        Ok(store) => store,
+        Err(e) => {
+            error!(
+                "Failed to get settings store: {}. Returning default templates.",
+                e
+            );
+            return default_prompt_templates();
+        }
+    };
+
+    match store.get(PROMPT_TEMPLATES_KEY) {
+        Some(value) => match serde_json::from_value::<Vec<PromptTemplate>>(value) {
+            Ok(templates) => templates,
+            Err(e) => {
+                error!(
+                    "Failed to deserialize prompt templates: {}. Returning default templates.",
+                    e
+                );
+                default_prompt_templates()
+            }
+        },
+        None => {
+            debug!("No prompt templates saved yet. Returning default templates.");
+            default_prompt_templates()
+        }
+    }
+}
+
+/// Saves the prompt templates after checking them for unknown placeholders
+/// and duplicate names; nothing is written if any of them is invalid.
+pub fn save_prompt_templates_internal(
+    app_handle: &AppHandle,
+    templates: &[PromptTemplate],
+) -> Result<(), String> {
+    export::validate_templates(templates)?;
+
+    let store = app_handle
+        .store(Path::new(SETTINGS_STORE_FILENAME))
+        .map_err(|e| format!("Cannot open settings store: {}", e))?;
+    store.set(
+        PROMPT_TEMPLATES_KEY,
+        serde_json::to_value(templates).map_err(|e| e.to_string())?,
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to write prompt templates to disk: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_1_settings_get_the_default_ignore_patterns() {
+        let mut settings = serde_json::from_value::<ApplicationSettings>(serde_json::json!({
+            "schemaVersion": 1,
+            "appVersion": "0.9.0",
+            "treeOption": "selected",
+            "ignorePatterns": [],
+        }))
+        .unwrap();
+        migrate(&mut settings);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert_eq!(settings.ignore_patterns, default_ignore_patterns());
+        assert!(settings.ignore_patterns.iter().any(|p| p == "*.lock"));
+        assert_eq!(settings.tree_option, "selected");
+    }
+
+    #[test]
+    fn current_settings_keep_their_ignore_patterns() {
+        let mut settings = ApplicationSettings {
+            ignore_patterns: vec!["*.log".to_string()],
+            ..ApplicationSettings::default()
+        };
+        migrate(&mut settings);
+        assert_eq!(settings.ignore_patterns, vec!["*.log".to_string()]);
+    }
+}
@@ -1,15 +1,63 @@
 use crate::services::cache_service::{self, CacheState};
 use futures::future::join_all;
-use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
 use tauri::{AppHandle, State};
 use tiktoken_rs::{self, CoreBPE};
 use tracing::{debug, error, info};
 
-use crate::domain::file_tree_node::FileTreeNode;
+use crate::domain::{
+    content_transforms::{ContentTransforms, TransformSavings},
+    file_selection::FileSelection,
+    file_size_cap::FileSizeCap,
+    file_tree_node::FileTreeNode,
+};
+use crate::services::encoding_service;
+use crate::services::export::{self, content::BYTES_PER_TOKEN};
 use crate::services::file_service::is_likely_binary_file;
+use crate::services::settings_service;
 
 static TOKEN_RPC_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
+static BPE: OnceLock<Arc<CoreBPE>> = OnceLock::new();
+
+/// The encoding behind every token count, as named in export headers.
+pub const TOKENIZER: &str = "o200k_base (gpt-4o)";
+
+/// The gpt-4o tokenizer, loaded on first use and shared from then on. Loading
+/// it parses the whole vocabulary, which is too slow to repeat on every call.
+pub fn shared_bpe() -> Result<Arc<CoreBPE>, String> {
+    if let Some(bpe) = BPE.get() {
+        return Ok(bpe.clone());
+    }
+    let bpe = tiktoken_rs::get_bpe_from_model("gpt-4o")
+        .map_err(|e| format!("Failed to initialize tokenizer: {}", e))?;
+    // Another thread may have won the race, either copy is fine
+    Ok(BPE.get_or_init(|| Arc::new(bpe)).clone())
+}
+
+// Token count of a whole file as it would be exported. Files over the size cap
+// aren't read at all; only their head and tail would be exported, so they are
+// estimated at what the cap lets through.
+fn count_file_tokens(path: &Path, size_cap: &FileSizeCap, bpe: &CoreBPE) -> io::Result<usize> {
+    let size = fs::metadata(path)?.len();
+    if size_cap.exceeds_bytes(size) {
+        let estimate = (size_cap.max_bytes / BYTES_PER_TOKEN) as usize;
+        return Ok(estimate.min(size_cap.max_tokens));
+    }
+    let content = encoding_service::read_text_file(path)?;
+    let tokens = bpe.encode_with_special_tokens(&content).len();
+    Ok(if size_cap.enabled {
+        tokens.min(size_cap.max_tokens)
+    } else {
+        tokens
+    })
+}
+
 /// Calculate tokens for a specific file, using the cache if possible.
 pub async fn calculate_file_tokens(
     file_path: String,
@@ -37,18 +85,10 @@ pub async fn calculate_file_tokens(
     }
 
     info!("Cache miss/stale for {}. Calculating tokens...", file_path);
-    let content = match fs::read_to_string(&path) {
-        Ok(content) => content,
-        Err(e) => return Err(format!("Failed to read file: {}", e)),
-    };
-
-    let bpe = match tiktoken_rs::get_bpe_from_model("gpt-4o") {
-        Ok(bpe) => bpe,
-        Err(e) => return Err(format!("Failed to initialize tokenizer: {}", e)),
-    };
-
-    let encoded = bpe.encode_with_special_tokens(&content);
-    let token_count = encoded.len();
+    let size_cap = settings_service::load_application_settings_internal(app_handle).file_size_cap;
+    let bpe = shared_bpe()?;
+    let token_count = count_file_tokens(&path, &size_cap, &bpe)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
 
     cache_service::update_cache(
         file_path.clone(),
@@ -68,10 +108,8 @@ pub async fn calculate_tokens_for_files(
 ) -> Result<HashMap<String, usize>, String> {
     let n = TOKEN_RPC_CALLS.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
     tracing::debug!("calculate_tokens_for_files() call #{}", n);
-    let bpe = Arc::new(
-        tiktoken_rs::get_bpe_from_model("gpt-4o")
-            .map_err(|e| format!("Failed to initialize tokenizer: {}", e))?,
-    );
+    let bpe = shared_bpe()?;
+    let size_cap = settings_service::load_application_settings_internal(app_handle).file_size_cap;
 
     let mut token_map = HashMap::new();
     let mut paths_to_calculate = Vec::new();
@@ -133,15 +171,13 @@ pub async fn calculate_tokens_for_files(
             let bpe_clone = bpe.clone();
 
             tasks.push(tokio::spawn(async move {
-                let content = match fs::read_to_string(&path) {
-                    Ok(c) => c,
+                match count_file_tokens(&path, &size_cap, &bpe_clone) {
+                    Ok(count) => (path_str, modified_secs, Ok(count)),
                     Err(e) => {
                         error!("Failed to read file {}: {}", path.display(), e);
-                        return (path_str, modified_secs, Err(e.to_string()));
+                        (path_str, modified_secs, Err(e.to_string()))
                     }
-                };
-                let encoded = bpe_clone.encode_with_special_tokens(&content);
-                (path_str, modified_secs, Ok(encoded.len()))
+                }
             }));
         }
 
@@ -196,9 +232,132 @@ pub async fn calculate_tokens_for_files(
     Ok(token_map)
 }
 
+// Calculate tokens for file selections. Whole files go through the cache as usual,
+// partial selections and outlines are counted on the content they actually export.
+pub async fn calculate_tokens_for_selections(
+    selections: &[FileSelection],
+    app_handle: &AppHandle,
+    cache_state: &State<'_, CacheState>,
+) -> Result<HashMap<String, usize>, String> {
+    let whole_files: Vec<String> = selections
+        .iter()
+        .filter(|s| s.is_whole_file())
+        .map(|s| s.path().to_string())
+        .collect();
+    let shaped: Vec<&FileSelection> = selections.iter().filter(|s| !s.is_whole_file()).collect();
+
+    let mut token_map = if whole_files.is_empty() {
+        HashMap::new()
+    } else {
+        calculate_tokens_for_files(whole_files, app_handle, cache_state).await?
+    };
+
+    if !shaped.is_empty() {
+        let bpe = shared_bpe()?;
+        let size_cap =
+            settings_service::load_application_settings_internal(app_handle).file_size_cap;
+        for selection in shaped {
+            let count =
+                count_selection_tokens(selection, &ContentTransforms::default(), &size_cap, &bpe);
+            token_map.insert(selection.path().to_string(), count);
+        }
+    }
+
+    Ok(token_map)
+}
+
+// Token count of a selection as it would appear in an export with `transforms` applied
+fn count_selection_tokens(
+    selection: &FileSelection,
+    transforms: &ContentTransforms,
+    size_cap: &FileSizeCap,
+    bpe: &CoreBPE,
+) -> usize {
+    let path = PathBuf::from(selection.path());
+    if is_likely_binary_file(&path) {
+        return 0;
+    }
+    let options = export::ContentOptions {
+        line_ranges: selection.line_ranges(),
+        mode: selection.mode(),
+        transforms,
+        file_name: selection.path(),
+        redactor: None,
+        size_cap: Some((size_cap, bpe)),
+        line_numbers: false,
+        token_limit: None,
+    };
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let read = if selection.line_ranges().is_empty() && size_cap.exceeds_bytes(size) {
+        encoding_service::read_head_and_tail(
+            &path,
+            size_cap.head_lines,
+            size_cap.tail_lines,
+            size_cap.max_bytes,
+        )
+        .map(|lines| export::export_head_and_tail(lines, &options))
+    } else {
+        encoding_service::read_text_file(&path)
+            .map(|content| export::export_text(content, &options))
+    };
+    match read {
+        Ok((text, _)) => bpe.encode_with_special_tokens(&text).len(),
+        Err(e) => {
+            error!("Failed to read file {}: {}", path.display(), e);
+            0
+        }
+    }
+}
+
+/// Token totals for the selections before and after applying `transforms`, so the
+/// UI can show what each transform saves. The "before" side uses the token cache.
+pub async fn calculate_transform_savings(
+    selections: &[FileSelection],
+    transforms: &ContentTransforms,
+    app_handle: &AppHandle,
+    cache_state: &State<'_, CacheState>,
+) -> Result<TransformSavings, String> {
+    let tokens_before = calculate_tokens_for_selections(selections, app_handle, cache_state)
+        .await?
+        .values()
+        .sum();
+    if transforms.is_empty() {
+        return Ok(TransformSavings {
+            tokens_before,
+            tokens_after: tokens_before,
+        });
+    }
+
+    let bpe = shared_bpe()?;
+    let size_cap = settings_service::load_application_settings_internal(app_handle).file_size_cap;
+    let mut tasks = Vec::new();
+    for selection in selections {
+        let selection = selection.clone();
+        let transforms = *transforms;
+        let bpe_clone = bpe.clone();
+        tasks.push(tokio::spawn(async move {
+            count_selection_tokens(&selection, &transforms, &size_cap, &bpe_clone)
+        }));
+    }
+
+    let mut tokens_after = 0;
+    for result in join_all(tasks).await {
+        match result {
+            Ok(count) => tokens_after += count,
+            Err(e) => error!("Tokio task join error: {}", e),
+        }
+    }
+
+    Ok(TransformSavings {
+        tokens_before,
+        tokens_after,
+    })
+}
+
 // Asynchronous function to fill token counts into an existing tree structure
 pub async fn fill_tokens_in_tree(
     nodes: &mut [FileTreeNode],
+    size_cap: FileSizeCap,
     bpe: Arc<CoreBPE>,
 ) -> Result<(), String> {
     let mut file_nodes: Vec<&mut FileTreeNode> = Vec::new();
@@ -230,15 +389,13 @@ pub async fn fill_tokens_in_tree(
                 debug!("Skipping binary file in fill_tokens_in_tree: {}", path);
                 return (path, 0); // Return 0 for binary files
             }
-            let content = match fs::read_to_string(&file_path) {
-                Ok(c) => c,
+            match count_file_tokens(&file_path, &size_cap, &bpe_clone) {
+                Ok(count) => (path, count),
                 Err(e) => {
                     eprintln!("Warning: Failed to read file {}: {}", path, e);
-                    return (path, 0); // Return 0 if read fails
+                    (path, 0) // Return 0 if read fails
                 }
-            };
-            let encoded = bpe_clone.encode_with_special_tokens(&content);
-            (path, encoded.len())
+            }
         }));
     }
 
@@ -259,6 +416,65 @@ pub async fn fill_tokens_in_tree(
             node.token_count = Some(*count);
         }
     }
+    sum_directory_tokens(nodes);
 
     Ok(())
 }
+
+// Sets every directory's token count to the total of the files below it, and
+// returns the total of `nodes`
+fn sum_directory_tokens(nodes: &mut [FileTreeNode]) -> usize {
+    nodes
+        .iter_mut()
+        .map(|node| {
+            if node.is_directory {
+                let total = node.children.as_deref_mut().map_or(0, sum_directory_tokens);
+                node.token_count = Some(total);
+            }
+            node.token_count.unwrap_or(0)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cap(max_bytes: u64, max_tokens: usize) -> FileSizeCap {
+        FileSizeCap {
+            enabled: true,
+            max_bytes,
+            max_tokens,
+            ..FileSizeCap::default()
+        }
+    }
+
+    #[test]
+    fn files_over_the_byte_cap_are_estimated() {
+        let path = std::env::temp_dir().join(format!("treesnap-count-{}", std::process::id()));
+        let text = "word ".repeat(2000);
+        fs::write(&path, &text).unwrap();
+        let bpe = shared_bpe().unwrap();
+        let exact = bpe.encode_with_special_tokens(&text).len();
+        let count = |size_cap: FileSizeCap| count_file_tokens(&path, &size_cap, &bpe).unwrap();
+
+        // 10,000 bytes against a 4,000 byte cap: 4,000 / 4 bytes per token
+        let estimated = count(cap(4000, 50_000));
+        // ... unless the token cap is lower still
+        let capped_estimate = count(cap(4000, 300));
+        // Under the byte cap the file is counted, then held to the token cap
+        let counted = count(cap(1_000_000, 50_000));
+        let capped = count(cap(1_000_000, 300));
+        let uncapped = count(FileSizeCap {
+            enabled: false,
+            ..cap(4000, 300)
+        });
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(estimated, 1000);
+        assert_eq!(capped_estimate, 300);
+        assert_eq!(counted, exact);
+        assert_eq!(capped, 300);
+        assert_eq!(uncapped, exact);
+    }
+}
@@ -1,11 +1,24 @@
-use crate::domain::file_tree_node::FileTreeNode;
+use crate::domain::{
+    application_settings::ApplicationSettings, file_size_cap::FileSizeCap,
+    file_tree_node::FileTreeNode,
+};
+use ignore::WalkState;
 use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
     fs::{self, Metadata},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
-use super::{file_service::build_ignore_list, token_service::fill_tokens_in_tree};
+use super::{
+    encoding_service,
+    export::PathDisplay,
+    file_service::{self, FileMapView},
+    ignore_service::{self, WorkspaceIgnore},
+    language_service,
+    token_service::{self, fill_tokens_in_tree},
+};
 
 // Helper function to get last modified time in seconds since UNIX_EPOCH
 fn get_last_modified_secs(metadata: Result<Metadata, std::io::Error>) -> Result<u64, String> {
@@ -18,79 +31,168 @@ fn get_last_modified_secs(metadata: Result<Metadata, std::io::Error>) -> Result<
         .map_err(|e| format!("System time is before UNIX EPOCH: {}", e))
 }
 
-// Synchronous recursive function to build the file tree structure
+// Builds the file tree structure under `dir`, the workspace itself or a
+// directory in it, synchronously. Ignored paths (see `WorkspaceIgnore`) and
+// empty directories are left out. With `max_depth` only that many levels are
+// built, directories on the last one have `has_children` set but no children.
+// The walk runs on a thread per CPU.
 pub fn build_tree_sync(
-    path: &Path,
-    base_dir: &Path,
-    ig: &ignore::gitignore::Gitignore,
+    workspace: &Path,
+    dir: &Path,
+    ignore_patterns: &[String],
+    language_overrides: &HashMap<String, String>,
+    max_depth: Option<usize>,
 ) -> Result<Vec<FileTreeNode>, String> {
-    let mut nodes = Vec::new();
-
-    let entries = fs::read_dir(path)
-        .map_err(|e| format!("Failed to read directory: {}: {}", path.display(), e))?;
+    build_tree_with_threads(
+        workspace,
+        dir,
+        ignore_patterns,
+        language_overrides,
+        max_depth,
+        0,
+    )
+}
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        let rel_path = path.strip_prefix(base_dir).unwrap_or(&path);
+// `build_tree_sync` on `threads` threads, 0 for one per CPU
+fn build_tree_with_threads(
+    workspace: &Path,
+    dir: &Path,
+    ignore_patterns: &[String],
+    language_overrides: &HashMap<String, String>,
+    max_depth: Option<usize>,
+    threads: usize,
+) -> Result<Vec<FileTreeNode>, String> {
+    let ignore = Arc::new(WorkspaceIgnore::new(workspace, ignore_patterns)?);
+    if ignore.is_ignored(dir, true) {
+        return Err(format!("Directory is ignored: {}", dir.display()));
+    }
+    // Nodes are made on the walk's threads, reading the start of every file
+    // for its language and encoding is most of the work
+    let walked: Mutex<Vec<(usize, PathBuf, FileTreeNode)>> = Mutex::new(Vec::new());
+    // Directories on the last level with something in them
+    let beyond_last_level: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+    ignore_service::workspace_walker(dir, ignore)
+        // One level further than the nodes go, to see into the last ones
+        .max_depth(max_depth.map(|depth| depth + 1))
+        .threads(threads)
+        .build_parallel()
+        .run(|| {
+            let walked = &walked;
+            let beyond_last_level = &beyond_last_level;
+            Box::new(move |result| {
+                match result {
+                    Ok(entry) if max_depth.is_some_and(|depth| entry.depth() > depth) => {
+                        if let Some(parent) = entry.path().parent() {
+                            beyond_last_level
+                                .lock()
+                                .unwrap()
+                                .insert(parent.to_path_buf());
+                        }
+                    }
+                    // The walk starts with the directory itself
+                    Ok(entry) if entry.depth() > 0 => {
+                        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                        let node = tree_node(entry.path(), is_dir, language_overrides);
+                        walked
+                            .lock()
+                            .unwrap()
+                            .push((entry.depth(), entry.into_path(), node));
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Warning: Skipping unreadable path: {}", e),
+                }
+                WalkState::Continue
+            })
+        });
 
-        // Skip if path is ignored
-        // Use is_dir() hint for matching
-        if ig.matched(rel_path, path.is_dir()).is_ignore() {
+    // Deepest first, so a directory's children are all collected by the time
+    // it's reached
+    let mut walked = walked.into_inner().unwrap();
+    let beyond_last_level = beyond_last_level.into_inner().unwrap();
+    walked.sort_by_key(|(depth, _, _)| Reverse(*depth));
+    let mut children_by_dir: HashMap<PathBuf, Vec<FileTreeNode>> = HashMap::new();
+    for (_, path, mut node) in walked {
+        let Some(parent) = path.parent() else {
             continue;
-        }
-
-        let metadata_result = entry.metadata();
-        let is_dir = metadata_result
-            .as_ref()
-            .map(|m| m.is_dir())
-            .unwrap_or(false);
-
-        let file_name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let last_modified = get_last_modified_secs(metadata_result).ok();
-
-        if is_dir {
-            // Recurse synchronously
-            match build_tree_sync(&path, base_dir, ig) {
-                Ok(children) if !children.is_empty() => {
-                    // Only add dir if it has non-ignored children
-                    nodes.push(FileTreeNode {
-                        name: file_name,
-                        path: path.to_string_lossy().to_string(),
-                        children: Some(children),
-                        is_directory: true,
-                        token_count: None, // Will be filled later if needed (though usually None for dirs)
-                        last_modified,
-                    });
+        };
+        if node.is_directory {
+            match children_by_dir.remove(&path) {
+                Some(mut children) => {
+                    sort_nodes(&mut children);
+                    node.children = Some(children);
                 }
-                Ok(_) => {}              // Skip empty directories
-                Err(e) => return Err(e), // Propagate errors
+                // Its children are loaded when it's expanded
+                None if beyond_last_level.contains(&path) => {}
+                // Only add dir if it has non-ignored children
+                None => continue,
             }
-        } else {
-            // Just record a file node for now
-            nodes.push(FileTreeNode {
-                name: file_name,
-                path: path.to_string_lossy().to_string(),
-                children: None,
-                is_directory: false,
-                token_count: None, // Will be filled later
-                last_modified,
-            });
+        }
+        children_by_dir
+            .entry(parent.to_path_buf())
+            .or_default()
+            .push(node);
+    }
+
+    let mut nodes = children_by_dir.remove(dir).unwrap_or_default();
+    sort_nodes(&mut nodes);
+    Ok(nodes)
+}
+
+// A node for a walked path, directories without their children yet
+fn tree_node(
+    path: &Path,
+    is_dir: bool,
+    language_overrides: &HashMap<String, String>,
+) -> FileTreeNode {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let metadata_result = fs::symlink_metadata(path);
+    let size = metadata_result.as_ref().map(|m| m.len()).ok();
+    let last_modified = get_last_modified_secs(metadata_result).ok();
+
+    if is_dir {
+        FileTreeNode {
+            name: file_name,
+            path: path.to_string_lossy().to_string(),
+            children: None,
+            is_directory: true,
+            // Empty directories are left out of the tree
+            has_children: true,
+            token_count: None, // Will be filled later if needed, as the total of its files
+            last_modified,
+            size: None,
+            language: None,
+            encoding: None,
+        }
+    } else {
+        // Just record a file node for now
+        FileTreeNode {
+            language: language_service::detect_file_language(path, language_overrides),
+            encoding: encoding_service::detect_file_encoding(path)
+                .map(|encoding| encoding.name().to_string()),
+            name: file_name,
+            path: path.to_string_lossy().to_string(),
+            children: None,
+            is_directory: false,
+            has_children: false,
+            token_count: None, // Will be filled later
+            last_modified,
+            size,
         }
     }
+}
 
-    // Sort directories first, then files
+// Sort directories first, then files
+fn sort_nodes(nodes: &mut [FileTreeNode]) {
     nodes.sort_by(|a, b| match (a.is_directory, b.is_directory) {
         (true, false) => std::cmp::Ordering::Less,
         (false, true) => std::cmp::Ordering::Greater,
         _ => a.name.cmp(&b.name),
     });
-
-    Ok(nodes)
 }
 
 // Helper function to filter tree to only include selected files
@@ -127,9 +229,18 @@ pub fn filter_tree_to_selected(tree: &mut Vec<FileTreeNode>, selected_paths: &[S
     tree.retain_mut(|node| filter_node(node, &selected_paths));
 }
 
+/// The workspace's file tree under a single root node, without what
+/// `ignore_patterns` or the workspace's ignore files leave out. File nodes get
+/// their language from `language_overrides` or the built-in detection, and
+/// files over `size_cap` get estimated token counts. With `max_depth` only the
+/// first levels are loaded, the rest comes from `get_directory_children`.
 pub async fn get_file_tree(
     dir_path: String,
     with_tokens_sync: bool,
+    ignore_patterns: &[String],
+    language_overrides: &HashMap<String, String>,
+    size_cap: FileSizeCap,
+    max_depth: Option<usize>,
 ) -> Result<Vec<FileTreeNode>, String> {
     let dir = PathBuf::from(&dir_path);
     if !dir.exists() || !dir.is_dir() {
@@ -139,9 +250,21 @@ pub async fn get_file_tree(
         ));
     }
 
-    let ig = build_ignore_list(&dir)?;
-    // Build the tree for the children first
-    let mut children_nodes = build_tree_sync(&dir, &dir, &ig)?;
+    // Build the tree for the children first, off the async runtime's threads
+    let walk_dir = dir.clone();
+    let ignore_patterns = ignore_patterns.to_vec();
+    let language_overrides = language_overrides.clone();
+    let mut children_nodes = tokio::task::spawn_blocking(move || {
+        build_tree_sync(
+            &walk_dir,
+            &walk_dir,
+            &ignore_patterns,
+            &language_overrides,
+            max_depth,
+        )
+    })
+    .await
+    .map_err(|e| format!("File tree task failed: {}", e))??;
 
     // Get root directory metadata and name
     let root_metadata = fs::metadata(&dir);
@@ -154,18 +277,15 @@ pub async fn get_file_tree(
     let root_path = dir.to_string_lossy().to_string();
 
     if with_tokens_sync {
-        let bpe = Arc::new(
-            tiktoken_rs::get_bpe_from_model("gpt-4o")
-                .map_err(|e| format!("Failed to initialize tokenizer: {}", e))?,
-        );
+        let bpe = token_service::shared_bpe()?;
 
         // Fill tokens for the children
-        fill_tokens_in_tree(&mut children_nodes, bpe).await?;
+        fill_tokens_in_tree(&mut children_nodes, size_cap, bpe).await?;
     }
 
     // Calculate root token count if tokens were calculated
     let root_token_count = if with_tokens_sync {
-        // Sum tokens from all children nodes. unwrap_or(0) handles potential None for child directories without countable files.
+        // Directories already hold the totals of their files
         Some(
             children_nodes
                 .iter()
@@ -177,6 +297,7 @@ pub async fn get_file_tree(
     };
 
     // Create the root node
+    let has_children = !children_nodes.is_empty();
     let root_node = FileTreeNode {
         name: root_name,
         path: root_path,
@@ -187,9 +308,550 @@ pub async fn get_file_tree(
             Some(children_nodes)
         },
         is_directory: true,
+        has_children,
         token_count: root_token_count,
         last_modified: Some(root_last_modified),
+        size: None,
+        language: None,
+        encoding: None,
     };
 
     Ok(vec![root_node]) // Return the single root node wrapped in a Vec
 }
+
+/// The children of `dir_path` in the workspace at `workspace_path`, for
+/// expanding a lazily loaded tree. They're ignored and sorted as in
+/// `build_tree_sync`, `depth` levels deep or all the way down for `None`.
+pub async fn get_directory_children(
+    workspace_path: String,
+    dir_path: String,
+    depth: Option<usize>,
+    ignore_patterns: &[String],
+    language_overrides: &HashMap<String, String>,
+) -> Result<Vec<FileTreeNode>, String> {
+    let workspace = PathBuf::from(&workspace_path);
+    let dir = PathBuf::from(&dir_path);
+    // Compared resolved, so neither `..` nor a symlink leads out of the workspace
+    let inside = match (fs::canonicalize(&workspace), fs::canonicalize(&dir)) {
+        (Ok(resolved_workspace), Ok(resolved_dir)) => resolved_dir.starts_with(resolved_workspace),
+        _ => false,
+    };
+    if !inside || !dir.is_dir() {
+        return Err(format!(
+            "Not a directory in the workspace {}: {}",
+            workspace_path, dir_path
+        ));
+    }
+
+    let ignore_patterns = ignore_patterns.to_vec();
+    let language_overrides = language_overrides.clone();
+    tokio::task::spawn_blocking(move || {
+        build_tree_sync(
+            &workspace,
+            &dir,
+            &ignore_patterns,
+            &language_overrides,
+            depth,
+        )
+    })
+    .await
+    .map_err(|e| format!("File tree task failed: {}", e))?
+}
+
+// Builds the file map text for an export according to the chosen tree option:
+// "include", "include-only-selected", "include-selected-paths" (the paths to the
+// selected files with counts of their siblings) or "do-not-include". The map is
+// drawn with the file map options and tree style in `settings`; annotated maps
+// count tokens for the files they show, with the file size cap deciding which
+// files are only estimated.
+pub async fn build_file_map(
+    dir_path: &str,
+    selected_file_paths: &[String],
+    tree_option: &str,
+    paths: &PathDisplay,
+    settings: &ApplicationSettings,
+) -> Result<Option<String>, String> {
+    let options = &settings.file_map;
+    let style = settings.tree_style;
+    let size_cap = settings.file_size_cap;
+    // The file map only shows names, languages don't matter here
+    let no_overrides = HashMap::new();
+    let get_tree = || {
+        get_file_tree(
+            dir_path.to_string(),
+            false,
+            &settings.ignore_patterns,
+            &no_overrides,
+            size_cap,
+            None,
+        )
+    };
+    let mut tree = match tree_option {
+        // Get full tree
+        "include" | "include-selected-paths" => get_tree().await?,
+        "include-only-selected" => {
+            // Get tree with only selected files
+            let mut tree = get_tree().await?;
+            filter_tree_to_selected(&mut tree, selected_file_paths);
+            tree
+        }
+        "do-not-include" => return Ok(None),
+        _ => return Err("Invalid tree option".to_string()),
+    };
+    // Counted after filtering, so directory totals only cover what the map shows
+    if options.annotate {
+        fill_tokens_in_tree(&mut tree, size_cap, token_service::shared_bpe()?).await?;
+    }
+    let view = FileMapView::new(
+        options,
+        style,
+        selected_file_paths,
+        tree_option == "include-selected-paths",
+    );
+    Ok(Some(file_service::generate_file_map(&tree, &view, paths)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        application_settings::default_ignore_patterns, file_map_options::FileMapOptions,
+        tree_style::TreeStyle,
+    };
+    use std::time::{Duration, Instant};
+
+    // A workspace shaped like a monorepo: packages of nested source directories,
+    // each with its own .gitignore and build output
+    fn write_fixture(root: &Path, packages: usize, modules: usize, files: usize) -> usize {
+        for package in 0..packages {
+            let package_dir = root.join(format!("packages/pkg{package}"));
+            for module in 0..modules {
+                let module_dir = package_dir.join(format!("src/module{module}"));
+                fs::create_dir_all(&module_dir).unwrap();
+                for file in 0..files {
+                    fs::write(
+                        module_dir.join(format!("file{file}.ts")),
+                        format!("export const value{file} = {file};\n"),
+                    )
+                    .unwrap();
+                }
+            }
+            fs::create_dir_all(package_dir.join("generated")).unwrap();
+            fs::write(package_dir.join("generated/out.js"), "ignored\n").unwrap();
+            fs::write(package_dir.join(".gitignore"), "generated/\n").unwrap();
+        }
+        packages * (modules * files + 1)
+    }
+
+    fn timed(root: &Path, threads: usize) -> (Vec<FileTreeNode>, Duration) {
+        let start = Instant::now();
+        let tree = build_tree_with_threads(
+            root,
+            root,
+            &default_ignore_patterns(),
+            &HashMap::new(),
+            None,
+            threads,
+        )
+        .unwrap();
+        (tree, start.elapsed())
+    }
+
+    // Run with `cargo test --release walk_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn walk_benchmark() {
+        let root = std::env::temp_dir().join(format!("treesnap-walk-{}", std::process::id()));
+        let file_count = write_fixture(&root, 40, 50, 25);
+        // Warm the file system cache so neither run pays for it
+        timed(&root, 0);
+
+        let (one_thread_tree, one_thread) = timed(&root, 1);
+        let (parallel_tree, parallel) = timed(&root, 0);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&one_thread_tree).unwrap(),
+            serde_json::to_string(&parallel_tree).unwrap()
+        );
+        println!(
+            "{} files: {:?} on one thread, {:?} in parallel ({:.1}x)",
+            file_count,
+            one_thread,
+            parallel,
+            one_thread.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn tree_keeps_directories_first_and_drops_empty_ones() {
+        let root = std::env::temp_dir().join(format!("treesnap-tree-{}", std::process::id()));
+        write_fixture(&root, 2, 2, 2);
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("README.md"), "# Fixture\n").unwrap();
+
+        let tree = build_tree_sync(
+            &root,
+            &root,
+            &default_ignore_patterns(),
+            &HashMap::new(),
+            None,
+        )
+        .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let names: Vec<&str> = tree.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["packages", "README.md"]);
+        let package = &tree[0].children.as_ref().unwrap()[0];
+        let package_names: Vec<&str> = package
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        // generated/ is in the package's .gitignore
+        assert_eq!(package_names, ["src", ".gitignore"]);
+    }
+
+    #[test]
+    fn lazy_tree_loads_directories_on_demand() {
+        let root = std::env::temp_dir().join(format!("treesnap-lazy-{}", std::process::id()));
+        write_fixture(&root, 2, 2, 2);
+        let patterns = default_ignore_patterns();
+
+        let tree = build_tree_sync(&root, &root, &patterns, &HashMap::new(), Some(2)).unwrap();
+        let package = &tree[0].children.as_ref().unwrap()[0];
+        assert_eq!(package.name, "pkg0");
+        assert!(package.has_children && package.children.is_none());
+
+        let children = build_tree_sync(
+            &root,
+            Path::new(&package.path),
+            &patterns,
+            &HashMap::new(),
+            Some(1),
+        )
+        .unwrap();
+        let ignored = build_tree_sync(
+            &root,
+            &Path::new(&package.path).join("generated"),
+            &patterns,
+            &HashMap::new(),
+            None,
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        let names: Vec<&str> = children.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["src", ".gitignore"]);
+        assert!(children[0].children.is_none());
+        assert!(ignored.is_err());
+    }
+
+    #[tokio::test]
+    async fn directory_children_stay_inside_the_workspace() {
+        let base = std::env::temp_dir().join(format!("treesnap-escape-{}", std::process::id()));
+        let root = base.join("ws");
+        let outside = base.join("outside");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(outside.join("secret.txt"), "hunter2\n").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        let overrides = HashMap::new();
+        let children = |dir: std::path::PathBuf| {
+            get_directory_children(
+                root.to_string_lossy().into_owned(),
+                dir.to_string_lossy().into_owned(),
+                None,
+                &[],
+                &overrides,
+            )
+        };
+        let inside = children(root.join("src")).await;
+        let dotted = children(root.join("src/../../outside")).await;
+        #[cfg(unix)]
+        let linked = children(root.join("link")).await;
+        fs::remove_dir_all(&base).unwrap();
+
+        assert_eq!(inside.unwrap()[0].name, "main.rs");
+        assert!(dotted.is_err());
+        #[cfg(unix)]
+        assert!(linked.is_err());
+    }
+
+    #[test]
+    fn file_nodes_carry_their_encoding() {
+        let root =
+            std::env::temp_dir().join(format!("treesnap-node-encoding-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("fn main() {}\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        fs::write(root.join("wide.rs"), utf16).unwrap();
+        let shift_jis = encoding_rs::SHIFT_JIS
+            .encode("日本語のテキストです。\nこれはシフトJISのファイルです。\n");
+        fs::write(root.join("notes.txt"), shift_jis.0).unwrap();
+        fs::write(root.join("plain.txt"), "plain\n").unwrap();
+        fs::write(root.join("image.bin"), [0x89, b'P', b'N', b'G', 0, 0, 0, 0]).unwrap();
+
+        let tree = build_tree_sync(&root, &root, &[], &HashMap::new(), None).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let encodings: Vec<(&str, Option<&str>)> = tree
+            .iter()
+            .map(|node| (node.name.as_str(), node.encoding.as_deref()))
+            .collect();
+        assert_eq!(
+            encodings,
+            [
+                ("image.bin", None),
+                ("notes.txt", Some("Shift_JIS")),
+                ("plain.txt", Some("UTF-8")),
+                ("wide.rs", Some("UTF-16LE")),
+            ]
+        );
+    }
+
+    // A small workspace for the file map: a chain of single directories, a
+    // directory of five files and a couple of source files
+    async fn file_map(
+        name: &str,
+        tree_option: &str,
+        selected: &[&str],
+        style: TreeStyle,
+        file_map: FileMapOptions,
+    ) -> String {
+        let root =
+            std::env::temp_dir().join(format!("treesnap-map-{}-{}", name, std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("deep/a/b")).unwrap();
+        fs::create_dir_all(root.join("assets")).unwrap();
+        fs::write(root.join("README.md"), "# Demo\n").unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    demo::run();\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn run() {}\n").unwrap();
+        fs::write(root.join("deep/a/b/leaf.txt"), "leaf\n").unwrap();
+        for i in 0..5 {
+            fs::write(root.join(format!("assets/icon{}.svg", i)), "<svg/>\n").unwrap();
+        }
+
+        let dir_path = root.to_string_lossy().to_string();
+        let selected: Vec<String> = selected
+            .iter()
+            .map(|path| root.join(path).to_string_lossy().to_string())
+            .collect();
+        let settings = ApplicationSettings {
+            file_map,
+            tree_style: style,
+            ..ApplicationSettings::default()
+        };
+        let map = build_file_map(
+            &dir_path,
+            &selected,
+            tree_option,
+            &PathDisplay::new(&dir_path, true, Some("demo")),
+            &settings,
+        )
+        .await;
+        fs::remove_dir_all(&root).unwrap();
+        map.unwrap().unwrap()
+    }
+
+    fn options(
+        annotate: bool,
+        max_depth: Option<usize>,
+        collapse_chains: bool,
+        elide_over: Option<usize>,
+    ) -> FileMapOptions {
+        FileMapOptions {
+            annotate,
+            max_depth,
+            collapse_chains,
+            elide_over,
+        }
+    }
+
+    #[tokio::test]
+    async fn file_map_annotations_sum_up_directories() {
+        let map = file_map(
+            "annotated",
+            "include",
+            &[],
+            TreeStyle::Ascii,
+            options(true, None, false, None),
+        )
+        .await;
+        assert_eq!(
+            map,
+            "\
+demo (35 tok)
+├── assets/ (15 tok)
+│   ├── icon0.svg (3 tok, 7 B)
+│   ├── icon1.svg (3 tok, 7 B)
+│   ├── icon2.svg (3 tok, 7 B)
+│   ├── icon3.svg (3 tok, 7 B)
+│   └── icon4.svg (3 tok, 7 B)
+├── deep/ (2 tok)
+│   └── a/ (2 tok)
+│       └── b/ (2 tok)
+│           └── leaf.txt (2 tok, 5 B)
+├── src/ (15 tok)
+│   ├── lib.rs (5 tok, 16 B)
+│   └── main.rs (10 tok, 31 B)
+└── README.md (3 tok, 7 B)
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_map_depth_limit_spares_selected_paths() {
+        let map = file_map(
+            "depth",
+            "include",
+            &["src/main.rs"],
+            TreeStyle::Ascii,
+            options(false, Some(1), false, None),
+        )
+        .await;
+        assert_eq!(
+            map,
+            "\
+demo
+├── assets/ (5 files)
+├── deep/ (1 file)
+├── src
+│   ├── lib.rs
+│   └── main.rs
+└── README.md
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_map_collapses_chains_and_elides_big_directories() {
+        let map = file_map(
+            "chains",
+            "include",
+            &[],
+            TreeStyle::Ascii,
+            options(false, None, true, Some(3)),
+        )
+        .await;
+        assert_eq!(
+            map,
+            "\
+demo
+├── assets/ (5 files)
+├── deep/a/b/
+│   └── leaf.txt
+├── src
+│   ├── lib.rs
+│   └── main.rs
+└── README.md
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_map_of_selected_paths_counts_the_rest() {
+        let map = file_map(
+            "selected",
+            "include-selected-paths",
+            &["src/main.rs"],
+            TreeStyle::Ascii,
+            FileMapOptions::default(),
+        )
+        .await;
+        assert_eq!(
+            map,
+            "\
+demo
+├── src
+│   ├── main.rs
+│   └── (1 other file)
+└── (2 other directories, 1 other file)
+"
+        );
+
+        let map = file_map(
+            "only",
+            "include-only-selected",
+            &["src/main.rs", "deep/a/b/leaf.txt"],
+            TreeStyle::Ascii,
+            FileMapOptions::default(),
+        )
+        .await;
+        assert_eq!(
+            map,
+            "\
+demo
+├── deep
+│   └── a
+│       └── b
+│           └── leaf.txt
+└── src
+    └── main.rs
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_map_styles() {
+        let shaped = options(false, None, true, Some(3));
+        let paths = file_map("paths", "include", &[], TreeStyle::PathList, shaped).await;
+        assert_eq!(
+            paths,
+            "demo\nassets/ (5 files)\ndeep/a/b/leaf.txt\nsrc/lib.rs\nsrc/main.rs\nREADME.md\n"
+        );
+
+        let markdown = file_map("markdown", "include", &[], TreeStyle::MarkdownList, shaped).await;
+        assert_eq!(
+            markdown,
+            "\
+demo
+- assets/ (5 files)
+- deep/a/b/
+  - leaf.txt
+- src
+  - lib.rs
+  - main.rs
+- README.md
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn json_file_map_is_json() {
+        let map = file_map(
+            "json",
+            "include-selected-paths",
+            &["src/lib.rs"],
+            TreeStyle::Json,
+            FileMapOptions::default(),
+        )
+        .await;
+        let nodes: serde_json::Value = serde_json::from_str(&map).unwrap();
+
+        let root = &nodes[0];
+        assert_eq!(
+            (&root["name"], &root["path"]),
+            (&"demo".into(), &"demo".into())
+        );
+        let src = &root["children"][0];
+        assert_eq!(root["children"].as_array().unwrap().len(), 1);
+        assert_eq!(src["path"], "src");
+        let lib = &src["children"][0];
+        assert_eq!(src["children"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            (&lib["path"], &lib["language"]),
+            (&"src/lib.rs".into(), &"rust".into())
+        );
+    }
+}
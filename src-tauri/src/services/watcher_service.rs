@@ -7,11 +7,11 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
-use tauri::{Emitter, State, Window};
+use tauri::{AppHandle, Emitter, State, Window};
 use tracing::debug;
 
-// Import the file service to use build_ignore_list
-use crate::services::file_service;
+use crate::services::ignore_service::{self, WorkspaceIgnore};
+use crate::services::settings_service;
 
 // State definition to hold the watcher
 pub struct WatcherState(pub Mutex<Option<RecommendedWatcher>>);
@@ -23,11 +23,13 @@ struct FileChangeEvent {
     kind: String, // e.g., "create", "remove", "modify", "rename"
 }
 
-// Internal function to start the watcher
+// Internal function to start the watcher, skipping paths that `ignore_patterns`
+// or the workspace's ignore files leave out
 // Takes a reference to the Mutex guarded Option<RecommendedWatcher>
 pub fn start_watcher_internal(
     window: Window,
     dir_path_str: String,
+    ignore_patterns: Vec<String>,
     watcher_mutex: &Mutex<Option<RecommendedWatcher>>,
 ) -> Result<(), String> {
     let base_dir = PathBuf::from(&dir_path_str);
@@ -35,8 +37,8 @@ pub fn start_watcher_internal(
         return Err(format!("Not a directory: {}", dir_path_str));
     }
 
-    // Build the ignore list for this directory
-    let ig = file_service::build_ignore_list(&base_dir)?;
+    // The same ignore rules the file tree is built with
+    let mut ig = WorkspaceIgnore::new(&base_dir, &ignore_patterns)?;
 
     // Clone base_dir for the move closure
     let watched_dir_path = base_dir.clone();
@@ -56,15 +58,26 @@ pub fn start_watcher_internal(
                 // Determine the primary event kind (simplified handling)
                 let kind = event.kind;
 
+                // An edited .gitignore or .treesnapignore changes what counts
+                // as ignored from here on
+                if event
+                    .paths
+                    .iter()
+                    .any(|path| ignore_service::is_ignore_file(path))
+                {
+                    match WorkspaceIgnore::new(&watched_dir_path, &ignore_patterns) {
+                        Ok(reloaded) => ig = reloaded,
+                        Err(e) => eprintln!("Warning: Failed to reload ignore rules: {}", e),
+                    }
+                }
+
                 // Filter out ignored paths
                 let non_ignored_paths: Vec<PathBuf> = event
                     .paths
                     .into_iter()
                     .filter(|path| {
-                        // Calculate path relative to the watched directory root
-                        let rel_path = path.strip_prefix(&watched_dir_path).unwrap_or(path);
-                        // Check if the path is ignored
-                        !ig.matched(rel_path, path.is_dir()).is_ignore()
+                        // Check if the path or a directory it's in is ignored
+                        !ig.is_ignored(path, path.is_dir())
                     })
                     .collect();
 
@@ -197,10 +210,13 @@ pub fn stop_watcher_internal(
 #[tauri::command]
 pub async fn start_watching_command(
     window: Window,
+    app_handle: AppHandle,
     dir_path: String,
     state: State<'_, WatcherState>,
 ) -> Result<(), String> {
-    start_watcher_internal(window, dir_path, &state.0) // Pass the inner Mutex
+    let settings = settings_service::load_application_settings_internal(&app_handle);
+    start_watcher_internal(window, dir_path, settings.ignore_patterns, &state.0)
+    // Pass the inner Mutex
 }
 
 // Tauri command to explicitly stop watching
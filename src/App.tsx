@@ -2,17 +2,30 @@ import { useState, useEffect } from "react";
 import { Panel, PanelGroup, PanelResizeHandle } from "react-resizable-panels";
 import "./resizable.css";
 import { SelectionSummary } from "./components/selection-summary";
-import { FileTreeNode } from "./types";
+import { ApplicationSettings, FileTreeNode } from "./types";
 import { TreeMap } from "./components/tree-map";
 import "react-tooltip/dist/react-tooltip.css";
-import { basename, toggleSelect } from "./utils";
+import {
+  basename,
+  hasUnloadedDirectories,
+  toggleSelect,
+  withChildren,
+} from "./utils";
 import { WorkspaceSelector } from "./components/workspace-selector";
-import { SidebarSummary, FileTree } from "./components/sidebar";
+import {
+  SidebarSummary,
+  FileTree,
+  IgnoreFileErrors,
+} from "./components/sidebar";
 import { TopBar } from "./components/top-bar";
 import { useRecentWorkspaces } from "./hooks/use-recent-workspaces";
 import { useWorkspace } from "./hooks/use-workspace";
 import { Settings } from "./components/settings";
-import { calculateTokensForFiles, openDirectoryDialog } from "@/platform";
+import {
+  calculateTokensForFiles,
+  getDirectoryChildren,
+  openDirectoryDialog,
+} from "@/platform";
 import { DebugLicenseControls } from "./components/debug";
 import { useLicense } from "./hooks/use-license";
 import { useApplicationSettings } from "./hooks/use-application-settings";
@@ -63,13 +76,32 @@ function App() {
     resetStates();
   }, [workspace.workspacePath]);
 
+  const handleSaveSettings = async (newSettings: ApplicationSettings) => {
+    // The backend rebuilds the tree and the watcher if the ignore patterns changed
+    const { tree } = await saveSettings(
+      newSettings,
+      workspace.status === "loaded" ? workspace.workspacePath : null
+    );
+    if (tree) {
+      workspace.fileTree.replaceTree(tree);
+    }
+  };
+
   const handleFileSelect = async (node: FileTreeNode) => {
     try {
-      const newSelection = toggleSelect(
-        node,
-        workspace.fileTree.data,
-        workspace.selectedFiles
-      );
+      let tree = workspace.fileTree.data;
+      // Selecting a directory selects everything in it, so a lazily loaded
+      // one needs the rest of its subtree first
+      if (hasUnloadedDirectories(node)) {
+        const children = await getDirectoryChildren(
+          workspace.workspacePath,
+          node.path,
+          null
+        );
+        tree = withChildren(tree, node.path, children);
+        workspace.fileTree.setChildren(node.path, children);
+      }
+      const newSelection = toggleSelect(node, tree, workspace.selectedFiles);
       if (newSelection) {
         workspace.setSelectedFiles(newSelection);
       }
@@ -174,7 +206,7 @@ function App() {
           <Settings
             settings={settings}
             onClose={() => setIsShowingSettings(!isShowingSettings)}
-            onSave={saveSettings}
+            onSave={handleSaveSettings}
           />
         )}
         <PanelGroup direction="horizontal" className="flex-1">
@@ -212,6 +244,9 @@ function App() {
                       fileTree={workspace.fileTree.data}
                       onRefresh={handleRefresh}
                     />
+                    <IgnoreFileErrors
+                      errors={workspace.fileTree.ignoreFileErrors}
+                    />
                   </div>
 
                   {/* Scrollable Content */}
@@ -302,6 +337,21 @@ function App() {
                             onSorted={(files) => {
                               workspace.setSelectedFiles(files);
                             }}
+                            onToggleOutline={(file) => {
+                              workspace.setSelectedFiles((prev) =>
+                                prev.map((f) =>
+                                  f.path === file.path
+                                    ? {
+                                        ...f,
+                                        contentMode:
+                                          f.contentMode === "outline"
+                                            ? "full"
+                                            : "outline",
+                                      }
+                                    : f
+                                )
+                              );
+                            }}
                             onDeselect={(file) => {
                               if (file.is_directory) {
                                 // If it's a directory header, deselect all files in that directory
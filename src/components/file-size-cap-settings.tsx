@@ -0,0 +1,70 @@
+import { ApplicationSettings, FileSizeCap } from "../types";
+
+const NUMBER_FIELDS: {
+  key: Exclude<keyof FileSizeCap, "enabled">;
+  label: string;
+  min: number;
+  step: number;
+}[] = [
+  { key: "maxBytes", label: "Max bytes", min: 1024, step: 1024 },
+  { key: "maxTokens", label: "Max tokens", min: 100, step: 1000 },
+  { key: "headLines", label: "First lines kept", min: 0, step: 10 },
+  { key: "tailLines", label: "Last lines kept", min: 0, step: 10 },
+];
+
+export const FileSizeCapSettings = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const cap = settings.fileSizeCap;
+  const save = (fileSizeCap: FileSizeCap) =>
+    onSave({ ...settings, fileSizeCap });
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Large Files
+      </h3>
+      <div className="flex items-center gap-3 text-sm">
+        <input
+          type="checkbox"
+          id="file-size-cap-enabled"
+          checked={cap.enabled}
+          onChange={(e) => save({ ...cap, enabled: e.target.checked })}
+          className="form-checkbox text-blue-500 bg-gray-700 border-gray-600"
+        />
+        <label
+          htmlFor="file-size-cap-enabled"
+          className="cursor-pointer text-gray-300"
+        >
+          Keep only the start and end of oversized files
+        </label>
+      </div>
+      {cap.enabled && (
+        <div className="grid grid-cols-2 gap-2 text-sm">
+          {NUMBER_FIELDS.map(({ key, label, min, step }) => (
+            <label key={key} className="flex items-center gap-2 text-gray-300">
+              <span className="w-32">{label}</span>
+              <input
+                type="number"
+                min={min}
+                step={step}
+                value={cap[key]}
+                onChange={(e) =>
+                  save({
+                    ...cap,
+                    [key]: Math.max(min, Number(e.target.value) || 0),
+                  })
+                }
+                className="w-28 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+            </label>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
@@ -0,0 +1,64 @@
+import { useState, useEffect } from "react";
+import { RotateCcw } from "lucide-react";
+import { ApplicationSettings } from "../types";
+import { getDefaultIgnorePatterns } from "@/platform";
+
+// One gitignore-style pattern per line
+const parse = (text: string) =>
+  text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0);
+
+export const IgnorePatterns = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const [draft, setDraft] = useState("");
+
+  useEffect(() => {
+    setDraft(settings.ignorePatterns.join("\n"));
+  }, [settings.ignorePatterns]);
+
+  const save = (ignorePatterns: string[]) => {
+    // Every change reloads the workspace, so skip saves that change nothing
+    if (ignorePatterns.join("\n") !== settings.ignorePatterns.join("\n")) {
+      onSave({ ...settings, ignorePatterns });
+    }
+  };
+
+  const resetToDefaults = async () => {
+    save(await getDefaultIgnorePatterns());
+  };
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Ignored Files
+      </h3>
+      <textarea
+        rows={6}
+        placeholder={"node_modules/\n*.lock"}
+        value={draft}
+        onChange={(e) => setDraft(e.target.value)}
+        onBlur={() => save(parse(draft))}
+        className="w-full bg-gray-700 border border-gray-600 rounded px-2 py-1 text-xs text-gray-300 font-mono"
+      />
+      <p className="text-xs text-gray-400">
+        Left out of every workspace on top of what git ignores, one gitignore
+        pattern per line. A <code>.treesnapignore</code> file in the workspace
+        can bring them back with <code>!pattern</code>.
+      </p>
+      <button
+        onClick={resetToDefaults}
+        className="cursor-pointer flex items-center gap-1 px-3 py-1 bg-gray-700 hover:bg-gray-600 rounded text-sm text-white border border-gray-500"
+      >
+        <RotateCcw size={14} />
+        <span>Reset to defaults</span>
+      </button>
+    </div>
+  );
+};
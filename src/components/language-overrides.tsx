@@ -0,0 +1,54 @@
+import { useState, useEffect } from "react";
+import { ApplicationSettings } from "../types";
+
+// One "key = language" pair per line
+const toText = (overrides: Record<string, string>) =>
+  Object.entries(overrides)
+    .map(([key, language]) => `${key} = ${language}`)
+    .join("\n");
+
+const parse = (text: string): Record<string, string> => {
+  const overrides: Record<string, string> = {};
+  for (const line of text.split("\n")) {
+    const [key, language] = line.split("=").map((part) => part.trim());
+    if (key && language) {
+      overrides[key] = language;
+    }
+  }
+  return overrides;
+};
+
+export const LanguageOverrides = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const [draft, setDraft] = useState("");
+
+  useEffect(() => {
+    setDraft(toText(settings.languageOverrides));
+  }, [settings.languageOverrides]);
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Code Fence Languages
+      </h3>
+      <textarea
+        rows={3}
+        placeholder={"Jenkinsfile = groovy\n*.h = cpp"}
+        value={draft}
+        onChange={(e) => setDraft(e.target.value)}
+        onBlur={() =>
+          onSave({ ...settings, languageOverrides: parse(draft) })
+        }
+        className="w-full bg-gray-700 border border-gray-600 rounded px-2 py-1 text-xs text-gray-300 font-mono"
+      />
+      <p className="text-xs text-gray-400">
+        Overrides the detected language, by file name or extension pattern.
+      </p>
+    </div>
+  );
+};
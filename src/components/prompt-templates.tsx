@@ -0,0 +1,122 @@
+import { useState, useEffect } from "react";
+import { Plus, Trash2 } from "lucide-react";
+import { ApplicationSettings, PromptTemplate } from "../types";
+import { usePromptTemplates } from "../hooks/use-prompt-templates";
+
+const PLACEHOLDER_HELP =
+  "{{file_map}} {{file_contents}} {{instructions}} {{token_count}} {{workspace_name}}";
+
+export const PromptTemplates = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const { templates, error, saveTemplates } = usePromptTemplates();
+  const [draft, setDraft] = useState<PromptTemplate[]>([]);
+
+  useEffect(() => {
+    setDraft(templates);
+  }, [templates]);
+
+  const updateDraft = (index: number, changes: Partial<PromptTemplate>) => {
+    setDraft((prev) =>
+      prev.map((template, i) =>
+        i === index ? { ...template, ...changes } : template
+      )
+    );
+  };
+
+  const handleSave = async () => {
+    const saved = await saveTemplates(draft);
+    // Stop wrapping exports in a template that no longer exists
+    if (
+      saved &&
+      settings.promptTemplate &&
+      !draft.some((t) => t.name === settings.promptTemplate)
+    ) {
+      onSave({ ...settings, promptTemplate: null });
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Prompt Templates
+      </h3>
+      <div className="flex items-center gap-3 text-sm">
+        <label htmlFor="prompt-template" className="text-gray-300">
+          Wrap exports in
+        </label>
+        <select
+          id="prompt-template"
+          value={settings.promptTemplate ?? ""}
+          onChange={(e) =>
+            onSave({ ...settings, promptTemplate: e.target.value || null })
+          }
+          className="bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+        >
+          <option value="">No template</option>
+          {templates.map((template) => (
+            <option key={template.name} value={template.name}>
+              {template.name}
+            </option>
+          ))}
+        </select>
+      </div>
+
+      <div className="space-y-2 max-h-64 overflow-y-auto">
+        {draft.map((template, index) => (
+          <div key={index} className="space-y-1">
+            <div className="flex items-center gap-2">
+              <input
+                type="text"
+                placeholder="Template name"
+                value={template.name}
+                onChange={(e) => updateDraft(index, { name: e.target.value })}
+                className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+              <button
+                onClick={() =>
+                  setDraft((prev) => prev.filter((_, i) => i !== index))
+                }
+                className="p-1 rounded bg-gray-700 hover:bg-gray-600 text-gray-400 hover:text-white"
+                title="Delete template"
+              >
+                <Trash2 size={14} />
+              </button>
+            </div>
+            <textarea
+              rows={4}
+              value={template.body}
+              onChange={(e) => updateDraft(index, { body: e.target.value })}
+              className="w-full bg-gray-700 border border-gray-600 rounded px-2 py-1 text-xs text-gray-300 font-mono"
+            />
+          </div>
+        ))}
+      </div>
+
+      <p className="text-xs text-gray-400">Placeholders: {PLACEHOLDER_HELP}</p>
+      {error && <p className="text-sm text-red-400">{error}</p>}
+
+      <div className="flex gap-2">
+        <button
+          onClick={() =>
+            setDraft((prev) => [...prev, { name: "", body: "{{file_contents}}" }])
+          }
+          className="cursor-pointer flex items-center gap-1 px-3 py-1 bg-gray-700 hover:bg-gray-600 rounded text-sm text-white border border-gray-500"
+        >
+          <Plus size={14} />
+          <span>Add template</span>
+        </button>
+        <button
+          onClick={handleSave}
+          className="cursor-pointer px-3 py-1 bg-blue-600 hover:bg-blue-700 rounded text-sm text-white"
+        >
+          Save templates
+        </button>
+      </div>
+    </div>
+  );
+};
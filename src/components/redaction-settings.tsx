@@ -0,0 +1,119 @@
+import { useState, useEffect } from "react";
+import { Plus, Trash2 } from "lucide-react";
+import { ApplicationSettings, RedactionRule } from "../types";
+import { validateRedactionRules } from "@/platform";
+
+export const RedactionSettings = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const [draft, setDraft] = useState<RedactionRule[]>([]);
+  // Message from the last save, e.g. a pattern that doesn't compile
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setDraft(settings.redaction.customRules);
+  }, [settings.redaction.customRules]);
+
+  const updateDraft = (index: number, changes: Partial<RedactionRule>) => {
+    setDraft((prev) =>
+      prev.map((rule, i) => (i === index ? { ...rule, ...changes } : rule))
+    );
+  };
+
+  const handleSave = async () => {
+    const { error } = await validateRedactionRules(draft);
+    setError(error);
+    if (!error) {
+      onSave({
+        ...settings,
+        redaction: { ...settings.redaction, customRules: draft },
+      });
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Secret Redaction
+      </h3>
+      <div className="flex items-center gap-3 text-sm">
+        <input
+          type="checkbox"
+          id="redaction-enabled"
+          checked={settings.redaction.enabled}
+          onChange={(e) =>
+            onSave({
+              ...settings,
+              redaction: { ...settings.redaction, enabled: e.target.checked },
+            })
+          }
+          className="form-checkbox text-blue-500 bg-gray-700 border-gray-600"
+        />
+        <label
+          htmlFor="redaction-enabled"
+          className="cursor-pointer text-gray-300"
+        >
+          Redact API keys, tokens, private keys and .env values
+        </label>
+      </div>
+
+      <div className="space-y-2">
+        {draft.map((rule, index) => (
+          <div key={index} className="flex items-center gap-2">
+            <input
+              type="text"
+              placeholder="Name"
+              value={rule.name}
+              onChange={(e) => updateDraft(index, { name: e.target.value })}
+              className="w-32 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+            />
+            <input
+              type="text"
+              placeholder="Regular expression"
+              value={rule.pattern}
+              onChange={(e) => updateDraft(index, { pattern: e.target.value })}
+              className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300 font-mono"
+            />
+            <button
+              onClick={() =>
+                setDraft((prev) => prev.filter((_, i) => i !== index))
+              }
+              className="p-1 rounded bg-gray-700 hover:bg-gray-600 text-gray-400 hover:text-white"
+              title="Delete rule"
+            >
+              <Trash2 size={14} />
+            </button>
+          </div>
+        ))}
+      </div>
+
+      <p className="text-xs text-gray-400">
+        Matches are replaced with [REDACTED:name]. With a capture group only
+        the group is replaced.
+      </p>
+      {error && <p className="text-sm text-red-400">{error}</p>}
+
+      <div className="flex gap-2">
+        <button
+          onClick={() =>
+            setDraft((prev) => [...prev, { name: "", pattern: "" }])
+          }
+          className="cursor-pointer flex items-center gap-1 px-3 py-1 bg-gray-700 hover:bg-gray-600 rounded text-sm text-white border border-gray-500"
+        >
+          <Plus size={14} />
+          <span>Add rule</span>
+        </button>
+        <button
+          onClick={handleSave}
+          className="cursor-pointer px-3 py-1 bg-blue-600 hover:bg-blue-700 rounded text-sm text-white"
+        >
+          Save rules
+        </button>
+      </div>
+    </div>
+  );
+};
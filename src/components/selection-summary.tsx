@@ -8,6 +8,7 @@ import {
   ArrowUp01Icon,
   ChevronsUpDown,
   ChevronsDownUp,
+  ListTree,
 } from "lucide-react";
 import classNames from "classnames";
 import { formatTokens } from "../utils";
@@ -20,6 +21,7 @@ interface SelectedFilesProps {
   groupByDirectory?: boolean;
   onDeselect: (file: FileTreeNode) => void;
   onSorted: (files: FileTreeNode[]) => void;
+  onToggleOutline?: (file: FileTreeNode) => void;
 }
 
 export const SelectionSummary: React.FC<SelectedFilesProps> = ({
@@ -29,6 +31,7 @@ export const SelectionSummary: React.FC<SelectedFilesProps> = ({
   groupByDirectory = true,
   onDeselect,
   onSorted,
+  onToggleOutline,
 }) => {
   const [sortDirection, setSortDirection] = useState<"asc" | "desc">("desc");
   const [expandFolders, setExpandFolders] = useState(true);
@@ -256,6 +259,26 @@ export const SelectionSummary: React.FC<SelectedFilesProps> = ({
                         )}%)`}
                     </span>
                   )}
+                  {!isDirectoryHeader && onToggleOutline && (
+                    <span
+                      className={classNames("text-sm", {
+                        "text-blue-400": file.contentMode === "outline",
+                        "text-gray-400": file.contentMode !== "outline",
+                      })}
+                    >
+                      <button
+                        className="bg-transparent border-none flex items-center cursor-pointer"
+                        onClick={() => onToggleOutline(file)}
+                        title={
+                          file.contentMode === "outline"
+                            ? "Exporting outline only, click for full content"
+                            : "Export outline only (signatures and doc comments)"
+                        }
+                      >
+                        <ListTree size={16} />
+                      </button>
+                    </span>
+                  )}
                   <span className="text-gray-400 text-sm">
                     <button
                       className="bg-transparent border-none flex items-center cursor-pointer"
@@ -1,9 +1,29 @@
 import { useState, useEffect } from "react";
 import { X, ShareIcon } from "lucide-react";
-import { getVersion, confirm, check, relaunch, openLink, __VSCODE__ } from "@/platform";
-import { ApplicationSettings } from "../types";
+import {
+  getVersion,
+  confirm,
+  check,
+  relaunch,
+  openLink,
+  __VSCODE__,
+  __WEB_DEMO__,
+} from "@/platform";
+import {
+  ApplicationSettings,
+  DiffOptions,
+  ExportFormat,
+  TreeStyle,
+} from "../types";
 import { useLicense } from "../hooks/use-license";
 import { LicenseArea } from "./license/license-area";
+import { PromptTemplates } from "./prompt-templates";
+import { RedactionSettings } from "./redaction-settings";
+import { LanguageOverrides } from "./language-overrides";
+import { FileSizeCapSettings } from "./file-size-cap-settings";
+import { IgnorePatterns } from "./ignore-patterns";
+import { TokenBudgetSettings } from "./token-budget-settings";
+import { TreeLoadDepth } from "./tree-load-depth";
 
 // Helper function to check if a date string is in the future
 const isDateInFuture = (dateString: string | null | undefined): boolean => {
@@ -184,6 +204,28 @@ export const Settings = ({
               Include only selected files in tree
             </label>
 
+            {!__VSCODE__ && !__WEB_DEMO__ && (
+              <>
+                <input
+                  type="radio"
+                  id="tree-include-selected-paths"
+                  name="treeOption"
+                  value="include-selected-paths"
+                  checked={settings.treeOption === "include-selected-paths"}
+                  onChange={() =>
+                    onSave({ ...settings, treeOption: "include-selected-paths" })
+                  }
+                  className="form-radio text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+                />
+                <label
+                  htmlFor="tree-include-selected-paths"
+                  className="cursor-pointer text-gray-300"
+                >
+                  Include paths to selected files, counting everything else
+                </label>
+              </>
+            )}
+
             {/* Radio Option 3 */}
             <input
               type="radio"
@@ -203,8 +245,427 @@ export const Settings = ({
               Do not include file tree
             </label>
           </div>
+          {!__VSCODE__ && !__WEB_DEMO__ && (
+            <select
+              id="tree-style"
+              value={settings.treeStyle}
+              disabled={settings.treeOption === "do-not-include"}
+              onChange={(e) =>
+                onSave({
+                  ...settings,
+                  treeStyle: e.target.value as TreeStyle,
+                })
+              }
+              className="bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+            >
+              <option value="ascii">ASCII tree</option>
+              <option value="path-list">Flat path list (fewest tokens)</option>
+              <option value="markdown-list">Markdown list</option>
+              <option value="json">JSON</option>
+            </select>
+          )}
+          {!__VSCODE__ && !__WEB_DEMO__ && (
+            <div className="grid grid-cols-[auto_1fr] gap-x-3 gap-y-2 text-sm">
+              <input
+                type="checkbox"
+                id="annotate-file-map"
+                checked={settings.fileMap.annotate}
+                disabled={settings.treeOption === "do-not-include"}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    fileMap: { ...settings.fileMap, annotate: e.target.checked },
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="annotate-file-map"
+                className="cursor-pointer text-gray-300"
+              >
+                Show token counts and sizes in the tree
+              </label>
+              <input
+                type="checkbox"
+                id="collapse-chains"
+                checked={settings.fileMap.collapseChains}
+                disabled={settings.treeOption === "do-not-include"}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    fileMap: {
+                      ...settings.fileMap,
+                      collapseChains: e.target.checked,
+                    },
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="collapse-chains"
+                className="cursor-pointer text-gray-300"
+              >
+                Collapse single-directory chains into one entry
+              </label>
+            </div>
+          )}
+          {!__VSCODE__ && !__WEB_DEMO__ && (
+            <div className="flex flex-wrap items-center gap-3 text-sm text-gray-300">
+              <label htmlFor="max-depth">Max depth</label>
+              <input
+                type="number"
+                id="max-depth"
+                min={1}
+                placeholder="No limit"
+                value={settings.fileMap.maxDepth ?? ""}
+                disabled={settings.treeOption === "do-not-include"}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    fileMap: {
+                      ...settings.fileMap,
+                      maxDepth: e.target.value
+                        ? Math.max(1, Number(e.target.value))
+                        : null,
+                    },
+                  })
+                }
+                className="w-24 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+              <label htmlFor="elide-over">Summarize folders over</label>
+              <input
+                type="number"
+                id="elide-over"
+                min={1}
+                placeholder="Never"
+                value={settings.fileMap.elideOver ?? ""}
+                disabled={settings.treeOption === "do-not-include"}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    fileMap: {
+                      ...settings.fileMap,
+                      elideOver: e.target.value
+                        ? Math.max(1, Number(e.target.value))
+                        : null,
+                    },
+                  })
+                }
+                className="w-24 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+              <span>files</span>
+            </div>
+          )}
         </div>
 
+        {/* Export Format Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div className="space-y-3">
+            <h3 className="text-base font-medium mb-2 text-gray-200">
+              Export Format
+            </h3>
+            <select
+              id="export-format"
+              value={settings.exportFormat}
+              onChange={(e) =>
+                onSave({
+                  ...settings,
+                  exportFormat: e.target.value as ExportFormat,
+                })
+              }
+              className="bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+            >
+              <option value="classic">Classic (File: path + code fences)</option>
+              <option value="xml">XML tags</option>
+              <option value="markdown">Markdown</option>
+              <option value="json">JSON</option>
+              <option value="plain-text">Plain text</option>
+            </select>
+            <div className="grid grid-cols-[auto_1fr] gap-x-3 gap-y-2 text-sm">
+              <input
+                type="checkbox"
+                id="relative-paths"
+                checked={settings.relativePaths}
+                onChange={(e) =>
+                  onSave({ ...settings, relativePaths: e.target.checked })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="relative-paths"
+                className="cursor-pointer text-gray-300"
+              >
+                Write paths relative to the workspace root
+              </label>
+              <input
+                type="checkbox"
+                id="line-numbers"
+                checked={settings.lineNumbers}
+                onChange={(e) =>
+                  onSave({ ...settings, lineNumbers: e.target.checked })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="line-numbers"
+                className="cursor-pointer text-gray-300"
+              >
+                Number every line, so answers can cite them
+              </label>
+              <input
+                type="checkbox"
+                id="metadata-header"
+                checked={settings.metadataHeaderFormats.includes(
+                  settings.exportFormat
+                )}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    metadataHeaderFormats: e.target.checked
+                      ? [...settings.metadataHeaderFormats, settings.exportFormat]
+                      : settings.metadataHeaderFormats.filter(
+                          (format) => format !== settings.exportFormat
+                        ),
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="metadata-header"
+                className="cursor-pointer text-gray-300"
+              >
+                Start exports in this format with a header: workspace, git
+                revision, file and token counts
+              </label>
+            </div>
+            {settings.relativePaths && (
+              <input
+                type="text"
+                id="root-display-name"
+                placeholder="Root display name (defaults to folder name)"
+                value={settings.rootDisplayName ?? ""}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    rootDisplayName: e.target.value || null,
+                  })
+                }
+                className="w-full bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+            )}
+          </div>
+        )}
+
+        {/* Content Transforms Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div className="space-y-3">
+            <h3 className="text-base font-medium mb-2 text-gray-200">
+              Content Transforms
+            </h3>
+            <div className="grid grid-cols-[auto_1fr] gap-x-3 gap-y-2 text-sm">
+              <input
+                type="checkbox"
+                id="strip-comments"
+                checked={settings.transforms.stripComments}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    transforms: {
+                      ...settings.transforms,
+                      stripComments: e.target.checked,
+                    },
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="strip-comments"
+                className="cursor-pointer text-gray-300"
+              >
+                Strip comments
+              </label>
+
+              <input
+                type="checkbox"
+                id="collapse-blank-lines"
+                checked={settings.transforms.collapseBlankLines}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    transforms: {
+                      ...settings.transforms,
+                      collapseBlankLines: e.target.checked,
+                    },
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="collapse-blank-lines"
+                className="cursor-pointer text-gray-300"
+              >
+                Collapse runs of blank lines
+              </label>
+
+              <input
+                type="checkbox"
+                id="trim-trailing-whitespace"
+                checked={settings.transforms.trimTrailingWhitespace}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    transforms: {
+                      ...settings.transforms,
+                      trimTrailingWhitespace: e.target.checked,
+                    },
+                  })
+                }
+                className="form-checkbox text-blue-500 bg-gray-700 border-gray-600 mt-1 self-start"
+              />
+              <label
+                htmlFor="trim-trailing-whitespace"
+                className="cursor-pointer text-gray-300"
+              >
+                Trim trailing whitespace
+              </label>
+            </div>
+          </div>
+        )}
+
+        {/* Ignored Files Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <IgnorePatterns settings={settings} onSave={onSave} />
+        )}
+
+        {/* Large Workspaces Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <TreeLoadDepth settings={settings} onSave={onSave} />
+        )}
+
+        {/* Code Fence Languages Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <LanguageOverrides settings={settings} onSave={onSave} />
+        )}
+
+        {/* Large Files Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <FileSizeCapSettings settings={settings} onSave={onSave} />
+        )}
+
+        {/* Token Budget Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <TokenBudgetSettings settings={settings} onSave={onSave} />
+        )}
+
+        {/* Split Exports Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div className="space-y-3">
+            <h3 className="text-base font-medium mb-2 text-gray-200">
+              Split Exports
+            </h3>
+            <div className="flex items-center gap-3 text-sm">
+              <label htmlFor="part-token-limit" className="text-gray-300">
+                Tokens per part
+              </label>
+              <input
+                type="number"
+                id="part-token-limit"
+                min={1000}
+                step={1000}
+                value={settings.partTokenLimit}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    partTokenLimit: Math.max(1000, Number(e.target.value) || 0),
+                  })
+                }
+                className="w-28 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+            </div>
+          </div>
+        )}
+
+        {/* Git Diff Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div className="space-y-3">
+            <h3 className="text-base font-medium mb-2 text-gray-200">
+              Git Diff
+            </h3>
+            <div className="flex items-center gap-3 text-sm">
+              <select
+                id="diff-against"
+                value={settings.diff.against}
+                onChange={(e) => {
+                  const contextLines = settings.diff.contextLines;
+                  const against = e.target.value as DiffOptions["against"];
+                  onSave({
+                    ...settings,
+                    diff:
+                      against === "ref"
+                        ? { against, ref: "main", contextLines }
+                        : { against, contextLines },
+                  });
+                }}
+                className="bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              >
+                <option value="head">Against HEAD</option>
+                <option value="staged">Staged changes only</option>
+                <option value="ref">Against a ref</option>
+              </select>
+              {settings.diff.against === "ref" && (
+                <input
+                  type="text"
+                  id="diff-ref"
+                  placeholder="Branch, tag or commit"
+                  value={settings.diff.ref}
+                  onChange={(e) =>
+                    onSave({
+                      ...settings,
+                      diff: {
+                        against: "ref",
+                        ref: e.target.value,
+                        contextLines: settings.diff.contextLines,
+                      },
+                    })
+                  }
+                  className="flex-1 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+                />
+              )}
+            </div>
+            <div className="flex items-center gap-3 text-sm">
+              <label htmlFor="diff-context-lines" className="text-gray-300">
+                Context lines
+              </label>
+              <input
+                type="number"
+                id="diff-context-lines"
+                min={0}
+                value={settings.diff.contextLines ?? 3}
+                onChange={(e) =>
+                  onSave({
+                    ...settings,
+                    diff: {
+                      ...settings.diff,
+                      contextLines: Math.max(0, Number(e.target.value) || 0),
+                    },
+                  })
+                }
+                className="w-20 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+              />
+            </div>
+          </div>
+        )}
+
+        {/* Prompt Templates Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <PromptTemplates settings={settings} onSave={onSave} />
+        )}
+
+        {/* Secret Redaction Section */}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <RedactionSettings settings={settings} onSave={onSave} />
+        )}
+
         <hr className="border-gray-600" />
 
         {/* License Section */}
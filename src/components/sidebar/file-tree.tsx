@@ -84,6 +84,12 @@ const FileIconAndLabel = ({
         )}
       </span>
       <span className="text-gray-300 truncate flex-1">{node.name}</span>
+      {/* Non-UTF-8 files are transcoded on export, say what they were read as */}
+      {node.encoding && node.encoding !== "UTF-8" && (
+        <span className="text-[10px] text-gray-500 flex-shrink-0">
+          {node.encoding}
+        </span>
+      )}
     </div>
   );
 };
@@ -0,0 +1,22 @@
+import { AlertTriangle } from "lucide-react";
+import { IgnoreFileError } from "../../types";
+
+// Ignore files with lines that couldn't be parsed, which otherwise just
+// leave those lines out
+export const IgnoreFileErrors = ({ errors }: { errors: IgnoreFileError[] }) => {
+  if (errors.length === 0) return null;
+
+  return (
+    <div
+      className="flex items-center gap-2 pl-4 mt-2 text-xs text-yellow-400"
+      title={errors.map((e) => `${e.path}\n${e.message}`).join("\n\n")}
+    >
+      <AlertTriangle size={14} className="flex-none" />
+      <span>
+        {errors.length === 1
+          ? "An ignore file has lines that couldn't be parsed"
+          : `${errors.length} ignore files have lines that couldn't be parsed`}
+      </span>
+    </div>
+  );
+};
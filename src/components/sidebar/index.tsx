@@ -1,3 +1,4 @@
 export * from "./sidebar-filter";
 export * from "./sidebar-summary";
 export * from "./file-tree";
+export * from "./ignore-file-errors";
@@ -0,0 +1,89 @@
+import { ApplicationSettings, BudgetPriority, TokenBudget } from "../types";
+
+const DEFAULT_BUDGET: TokenBudget = {
+  maxTokens: 100_000,
+  priority: "smallest-first",
+  truncateOverflow: false,
+};
+
+export const TokenBudgetSettings = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  const budget = settings.tokenBudget;
+  const save = (tokenBudget: TokenBudget | null) =>
+    onSave({ ...settings, tokenBudget });
+
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Token Budget
+      </h3>
+      <div className="flex items-center gap-3 text-sm">
+        <input
+          type="checkbox"
+          id="token-budget-enabled"
+          checked={budget !== null}
+          onChange={(e) => save(e.target.checked ? DEFAULT_BUDGET : null)}
+          className="form-checkbox text-blue-500 bg-gray-700 border-gray-600"
+        />
+        <label
+          htmlFor="token-budget-enabled"
+          className="cursor-pointer text-gray-300"
+        >
+          Leave out files once an export reaches a token budget
+        </label>
+      </div>
+      {budget && (
+        <div className="grid grid-cols-2 gap-2 text-sm">
+          <label className="flex items-center gap-2 text-gray-300">
+            <span className="w-32">Max tokens</span>
+            <input
+              type="number"
+              min={1000}
+              step={1000}
+              value={budget.maxTokens}
+              onChange={(e) =>
+                save({
+                  ...budget,
+                  maxTokens: Math.max(1000, Number(e.target.value) || 0),
+                })
+              }
+              className="w-28 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+            />
+          </label>
+          <label className="flex items-center gap-2 text-gray-300">
+            <span className="w-32">Keep first</span>
+            <select
+              value={budget.priority ?? "smallest-first"}
+              onChange={(e) =>
+                save({ ...budget, priority: e.target.value as BudgetPriority })
+              }
+              className="bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+            >
+              <option value="smallest-first">Smallest files</option>
+              <option value="selection-order">Files selected first</option>
+              <option value="most-recently-modified">
+                Most recently modified
+              </option>
+            </select>
+          </label>
+          <label className="col-span-2 flex items-center gap-2 text-gray-300">
+            <input
+              type="checkbox"
+              checked={budget.truncateOverflow ?? false}
+              onChange={(e) =>
+                save({ ...budget, truncateOverflow: e.target.checked })
+              }
+              className="form-checkbox text-blue-500 bg-gray-700 border-gray-600"
+            />
+            Cut the first file that doesn't fit instead of leaving it out
+          </label>
+        </div>
+      )}
+    </div>
+  );
+};
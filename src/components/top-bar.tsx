@@ -1,8 +1,18 @@
-import { Copy, FolderOpen, Settings, X } from "lucide-react";
-import { basename } from "../utils";
+import {
+  Copy,
+  FileDiff,
+  FileMinus,
+  Scissors,
+  FolderOpen,
+  Save,
+  Settings,
+  ShieldAlert,
+  X,
+} from "lucide-react";
+import { basename, formatTokens } from "../utils";
 import { FileTreeNode, ApplicationSettings } from "../types";
 import { useExport } from "../hooks/use-export";
-import { __VSCODE__ } from "@/platform";
+import { __VSCODE__, __WEB_DEMO__ } from "@/platform";
 
 export const TopBar = ({
   selectedFiles,
@@ -21,7 +31,20 @@ export const TopBar = ({
   settings: ApplicationSettings;
   setHasExportedForWeb: (hasExportedForWeb: boolean) => void;
 }) => {
-  const { status, copyExportToClipboard } = useExport({
+  const {
+    status,
+    copyExportToClipboard,
+    transformSavings,
+    redactions,
+    omittedFiles,
+    parts,
+    nextPart,
+    splitExport,
+    copyNextPart,
+    saveExportToFile,
+    instructions,
+    setInstructions,
+  } = useExport({
     selectedFiles,
     workspacePath,
     settings,
@@ -59,6 +82,119 @@ export const TopBar = ({
             Export Selection to Clipboard
           </span>
         </div>
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div
+            className={`flex items-center justify-center px-3 py-2 border-r border-gray-700
+                        cursor-pointer hover:bg-gray-700 h-full
+                        ${isDisabled ? "opacity-50 cursor-not-allowed" : ""}`}
+            onClick={() => {
+              if (isDisabled) return;
+              copyExportToClipboard(settings.treeOption, true);
+            }}
+            title={
+              isDisabled
+                ? "No files selected"
+                : "Copy the diff of the selected files"
+            }
+          >
+            <FileDiff size={16} className="text-blue-400" />
+            <span className="ml-2 text-gray-300">Export Diff</span>
+          </div>
+        )}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div
+            className={`flex items-center justify-center px-3 py-2 border-r border-gray-700
+                        cursor-pointer hover:bg-gray-700 h-full
+                        ${isDisabled ? "opacity-50 cursor-not-allowed" : ""}`}
+            onClick={() => {
+              if (isDisabled) return;
+              if (parts.length > 0) {
+                copyNextPart();
+              } else {
+                splitExport();
+              }
+            }}
+            title={
+              isDisabled
+                ? "No files selected"
+                : `Split the export into parts of at most ${formatTokens(
+                    settings.partTokenLimit
+                  )} tokens`
+            }
+          >
+            <Scissors size={16} className="text-blue-400" />
+            <span className="ml-2 text-gray-300">
+              {parts.length > 0
+                ? `Copy part ${nextPart + 1}/${parts.length}`
+                : "Split"}
+            </span>
+          </div>
+        )}
+        {!__VSCODE__ && !__WEB_DEMO__ && (
+          <div
+            className={`flex items-center justify-center px-3 py-2 border-r border-gray-700
+                        cursor-pointer hover:bg-gray-700 h-full
+                        ${isDisabled ? "opacity-50 cursor-not-allowed" : ""}`}
+            onClick={() => {
+              if (isDisabled) return;
+              saveExportToFile();
+            }}
+            title={
+              isDisabled ? "No files selected" : "Save the export to a file"
+            }
+          >
+            <Save size={16} className="text-blue-400" />
+            <span className="ml-2 text-gray-300">Save</span>
+          </div>
+        )}
+        {settings.promptTemplate && (
+          <div className="flex items-center px-3 py-2 border-r border-gray-700">
+            <input
+              type="text"
+              placeholder={`Instructions for "${settings.promptTemplate}"`}
+              value={instructions}
+              onChange={(e) => setInstructions(e.target.value)}
+              className="w-56 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-xs text-gray-300"
+            />
+          </div>
+        )}
+        {transformSavings && (
+          <div
+            className="flex items-center px-3 py-2 text-gray-300"
+            title="Tokens before and after content transforms"
+          >
+            {formatTokens(transformSavings.tokensBefore)} →{" "}
+            {formatTokens(transformSavings.tokensAfter)} tokens
+          </div>
+        )}
+        {omittedFiles.length > 0 && (
+          <div
+            className="flex items-center px-3 py-2 text-yellow-400"
+            title={omittedFiles
+              .map((f) => `${f.path} (${formatTokens(f.tokenCount)} tokens)`)
+              .join("\n")}
+          >
+            <FileMinus size={16} />
+            <span className="ml-2">
+              Left out {omittedFiles.length}{" "}
+              {omittedFiles.length === 1 ? "file" : "files"} over the budget
+            </span>
+          </div>
+        )}
+        {redactions.length > 0 && (
+          <div
+            className="flex items-center px-3 py-2 text-yellow-400"
+            title={redactions
+              .map((r) => `${r.path}:${r.line} ${r.kind}`)
+              .join("\n")}
+          >
+            <ShieldAlert size={16} />
+            <span className="ml-2">
+              Redacted {redactions.length}{" "}
+              {redactions.length === 1 ? "secret" : "secrets"}
+            </span>
+          </div>
+        )}
       </div>
 
       <div className="flex-1 flex items-center justify-center py-2 absolute left-1/2 -translate-x-1/2 h-full">
@@ -0,0 +1,42 @@
+import { ApplicationSettings } from "../types";
+
+export const TreeLoadDepth = ({
+  settings,
+  onSave,
+}: {
+  settings: ApplicationSettings;
+  onSave: (settings: ApplicationSettings) => void;
+}) => {
+  return (
+    <div className="space-y-3">
+      <h3 className="text-base font-medium mb-2 text-gray-200">
+        Large Workspaces
+      </h3>
+      <label className="flex items-center gap-2 text-sm text-gray-300">
+        <span className="w-32">Levels loaded</span>
+        <input
+          type="number"
+          min={1}
+          step={1}
+          placeholder="All"
+          value={settings.treeLoadDepth ?? ""}
+          onChange={(e) =>
+            onSave({
+              ...settings,
+              // Empty loads the whole tree up front
+              treeLoadDepth:
+                e.target.value === ""
+                  ? null
+                  : Math.max(1, Math.floor(Number(e.target.value)) || 1),
+            })
+          }
+          className="w-28 bg-gray-700 border border-gray-600 rounded px-2 py-1 text-sm text-gray-300"
+        />
+      </label>
+      <p className="text-xs text-gray-400">
+        Opening a workspace loads only this many levels of its tree, the rest
+        is loaded as folders are expanded. Leave empty to load everything.
+      </p>
+    </div>
+  );
+};
@@ -19,9 +19,13 @@ export const useApplicationSettings = () => {
     fetchSettings();
   }, []);
 
-  const saveSettings = (settings: ApplicationSettings) => {
+  // Passing the open workspace gets its tree rebuilt if the ignore patterns changed
+  const saveSettings = (
+    settings: ApplicationSettings,
+    workspacePath: string | null = null
+  ) => {
     setSettings(settings);
-    updateApplicationSettings(settings);
+    return updateApplicationSettings(settings, workspacePath);
   };
 
   return { settings, saveSettings };
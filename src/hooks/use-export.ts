@@ -1,8 +1,33 @@
-import { useState } from "react";
-import { FileTreeNode, ApplicationSettings } from "../types";
-import { copyFilesWithTreeToClipboard } from "@/platform";
+import { useEffect, useState } from "react";
+import {
+  FileTreeNode,
+  ApplicationSettings,
+  ExportOptions,
+  ExportPart,
+  FileSelection,
+  OmittedFile,
+  RedactedSecret,
+  TransformSavings,
+  TreeOption,
+} from "../types";
+import {
+  calculateTransformSavings,
+  copyFilesWithTreeToClipboard,
+  copyTextToClipboard,
+  exportToFile,
+  splitExportIntoParts,
+} from "@/platform";
 
-type TreeOption = "include" | "include-only-selected" | "do-not-include";
+
+// Outlined files carry their mode, everything else is sent as a plain path
+const toSelections = (selectedFiles: FileTreeNode[]): FileSelection[] =>
+  selectedFiles
+    .filter((file) => !file.is_directory)
+    .map((file) =>
+      file.contentMode === "outline"
+        ? { path: file.path, mode: "outline" }
+        : file.path
+    );
 
 export const useExport = ({
   selectedFiles,
@@ -16,27 +41,85 @@ export const useExport = ({
   const [status, setStatus] = useState<
     "idle" | "copying" | "success" | "error"
   >("idle");
+  // Fills {{instructions}} when exports are wrapped in a prompt template
+  const [instructions, setInstructions] = useState("");
+  const [transformSavings, setTransformSavings] =
+    useState<TransformSavings | null>(null);
+  // Secrets kept out of the last export
+  const [redactions, setRedactions] = useState<RedactedSecret[]>([]);
+  // Files the token budget kept out of the last export
+  const [omittedFiles, setOmittedFiles] = useState<OmittedFile[]>([]);
+  // Parts of the last split export, and the next one to copy
+  const [parts, setParts] = useState<ExportPart[]>([]);
+  const [nextPart, setNextPart] = useState(0);
+
+  const { stripComments, collapseBlankLines, trimTrailingWhitespace } =
+    settings.transforms;
+  const anyTransformEnabled =
+    stripComments || collapseBlankLines || trimTrailingWhitespace;
+
+  // Token counts before and after the transforms, so the savings can be shown
+  useEffect(() => {
+    const selections = toSelections(selectedFiles);
+    if (!anyTransformEnabled || selections.length === 0) {
+      setTransformSavings(null);
+      return;
+    }
+
+    let cancelled = false;
+    calculateTransformSavings(selections, settings.transforms)
+      .then((savings) => {
+        if (!cancelled) setTransformSavings(savings);
+      })
+      .catch(console.error);
+    return () => {
+      cancelled = true;
+    };
+  }, [
+    selectedFiles,
+    anyTransformEnabled,
+    stripComments,
+    collapseBlankLines,
+    trimTrailingWhitespace,
+  ]);
+
+  // A split export goes stale as soon as the selection changes
+  useEffect(() => {
+    setParts([]);
+    setNextPart(0);
+  }, [selectedFiles]);
+
+  const exportOptions = (asDiff: boolean): ExportOptions => ({
+    format: settings.exportFormat,
+    tokenBudget: settings.tokenBudget ?? undefined,
+    transforms: settings.transforms,
+    diff: asDiff ? settings.diff : undefined,
+    instructions: settings.promptTemplate ? instructions : undefined,
+  });
 
   // New function to handle the copy to clipboard with tree structure
   const copyExportToClipboard = async (
-    treeOption: TreeOption = settings.treeOption
+    treeOption: TreeOption = settings.treeOption,
+    // Export the diff of each file (see the Git Diff settings) instead of its content
+    asDiff = false
   ) => {
     setStatus("copying");
     try {
-      // Get the paths of all selected files
-      const selectedFilePaths = selectedFiles
-        .filter((file) => !file.is_directory)
-        .map((file) => file.path);
+      // Get the paths (and modes) of all selected files
+      const selections = toSelections(selectedFiles);
 
-      if (selectedFilePaths.length === 0) {
+      if (selections.length === 0) {
         throw new Error("No files selected to copy.");
       }
 
-      await copyFilesWithTreeToClipboard(
+      const summary = await copyFilesWithTreeToClipboard(
         workspacePath,
-        selectedFilePaths,
-        treeOption
+        selections,
+        treeOption,
+        exportOptions(asDiff)
       );
+      setRedactions(summary?.redactions ?? []);
+      setOmittedFiles(summary?.omittedFiles ?? []);
 
       // Show success feedback
       setStatus("success");
@@ -49,5 +132,87 @@ export const useExport = ({
     } finally {
     }
   };
-  return { status, copyExportToClipboard };
+  // Writes the export to a file picked in a save dialog instead of the clipboard
+  const saveExportToFile = async () => {
+    setStatus("copying");
+    try {
+      const selections = toSelections(selectedFiles);
+      if (selections.length === 0) {
+        throw new Error("No files selected to export.");
+      }
+
+      const summary = await exportToFile(
+        workspacePath,
+        selections,
+        settings.treeOption,
+        exportOptions(false)
+      );
+      if (summary) {
+        setRedactions(summary.redactions);
+        setOmittedFiles(summary.omittedFiles);
+      }
+    } catch (err) {
+      console.error("Failed to export to file:", err);
+    } finally {
+      setStatus("idle");
+    }
+  };
+
+  // Splits the export into parts under the part token limit from settings
+  const splitExport = async () => {
+    setStatus("copying");
+    try {
+      const selections = toSelections(selectedFiles);
+      if (selections.length === 0) {
+        throw new Error("No files selected to split.");
+      }
+
+      const split = await splitExportIntoParts(
+        workspacePath,
+        selections,
+        settings.treeOption,
+        settings.partTokenLimit,
+        exportOptions(false)
+      );
+      setParts(split.parts);
+      setNextPart(0);
+      setRedactions(split.redactions);
+    } catch (err) {
+      console.error("Failed to split export:", err);
+    } finally {
+      setStatus("idle");
+    }
+  };
+
+  // Copies the next part; once the last one is copied the split is done
+  const copyNextPart = async () => {
+    const part = parts[nextPart];
+    if (!part) return;
+    try {
+      await copyTextToClipboard(part.content);
+      if (nextPart + 1 < parts.length) {
+        setNextPart(nextPart + 1);
+      } else {
+        setParts([]);
+        setNextPart(0);
+      }
+    } catch (err) {
+      console.error("Failed to copy part:", err);
+    }
+  };
+
+  return {
+    status,
+    copyExportToClipboard,
+    transformSavings,
+    redactions,
+    omittedFiles,
+    parts,
+    nextPart,
+    splitExport,
+    copyNextPart,
+    saveExportToFile,
+    instructions,
+    setInstructions,
+  };
 };
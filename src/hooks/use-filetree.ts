@@ -1,6 +1,12 @@
-import { FileChangeEvent, FileTreeNode } from "../types";
+import { FileChangeEvent, FileTreeNode, IgnoreFileError } from "../types";
 import { useEffect, useState } from "react";
-import { closeWorkspace, openWorkspace, listen } from "@/platform";
+import {
+  closeWorkspace,
+  getIgnoreFileErrors,
+  openWorkspace,
+  listen,
+} from "@/platform";
+import { withChildren } from "../utils/tree-utils";
 
 export const useFileTree = (
   onFilesChanged: (files: FileChangeEvent[]) => void
@@ -11,6 +17,16 @@ export const useFileTree = (
     "idle"
   );
   const [error, setError] = useState<string | null>(null);
+  const [ignoreFileErrors, setIgnoreFileErrors] = useState<IgnoreFileError[]>(
+    []
+  );
+
+  const refreshIgnoreFileErrors = (dirPath: string) => {
+    // Broken lines in ignore files would otherwise just match nothing
+    getIgnoreFileErrors(dirPath)
+      .then(setIgnoreFileErrors)
+      .catch((err) => console.error("Error checking ignore files:", err));
+  };
 
   const loadFileTree = async (dirPath: string) => {
     if (status === "loading") return;
@@ -31,9 +47,21 @@ export const useFileTree = (
       setCurrentDirPath(dirPath);
       setStatus("loaded");
       console.log(`File tree loaded for: ${dirPath}`);
+      refreshIgnoreFileErrors(dirPath);
     }
   };
 
+  // Swaps in a tree the backend rebuilt, e.g. after the ignore patterns changed
+  const replaceTree = (tree: FileTreeNode[]) => {
+    setFileTree(tree);
+    if (currentDirPath) refreshIgnoreFileErrors(currentDirPath);
+  };
+
+  // Fills in a lazily loaded directory once its children are fetched
+  const setChildren = (path: string, children: FileTreeNode[]) => {
+    setFileTree((tree) => withChildren(tree, path, children));
+  };
+
   const close = async () => {
     if (currentDirPath) {
       try {
@@ -47,6 +75,7 @@ export const useFileTree = (
     setCurrentDirPath(null);
     setStatus("idle");
     setError(null);
+    setIgnoreFileErrors([]);
   };
 
   useEffect(() => {
@@ -93,8 +122,11 @@ export const useFileTree = (
   return {
     data: fileTree,
     loadFileTree,
+    setChildren,
+    replaceTree,
     status,
     error,
+    ignoreFileErrors,
     close,
     currentDirPath,
   };
@@ -0,0 +1,25 @@
+import { useState, useEffect } from "react";
+import { PromptTemplate } from "../types";
+import { getPromptTemplates, updatePromptTemplates } from "@/platform";
+
+export const usePromptTemplates = () => {
+  const [templates, setTemplates] = useState<PromptTemplate[]>([]);
+  // Validation message from the last save, e.g. an unknown placeholder
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getPromptTemplates().then(setTemplates).catch(console.error);
+  }, []);
+
+  // Resolves to whether the templates passed validation and were saved
+  const saveTemplates = async (next: PromptTemplate[]) => {
+    const { error } = await updatePromptTemplates(next);
+    setError(error);
+    if (!error) {
+      setTemplates(next);
+    }
+    return error === null;
+  };
+
+  return { templates, error, saveTemplates };
+};
@@ -1,6 +1,7 @@
 import { FileTreeNode } from "../types";
-import { useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import { useFileTree } from "./use-filetree";
+import { getDirectoryChildren } from "@/platform";
 type WorkspaceStatus = "not-loaded" | "loading" | "loaded" | "error";
 
 export const useWorkspace = (
@@ -27,6 +28,35 @@ export const useWorkspace = (
     }
   });
 
+  // Directories whose children are being fetched
+  const loadingFolders = useRef<Set<string>>(new Set());
+
+  // In a lazily loaded tree, expanding a directory fetches what's in it
+  useEffect(() => {
+    if (!workspacePath) return;
+
+    const visit = (nodes: FileTreeNode[]) => {
+      for (const node of nodes) {
+        if (!node.is_directory || !expandedFolders.has(node.path)) continue;
+        if (node.children) {
+          visit(node.children);
+        } else if (
+          node.has_children &&
+          !loadingFolders.current.has(node.path)
+        ) {
+          loadingFolders.current.add(node.path);
+          getDirectoryChildren(workspacePath, node.path, 1)
+            .then((children) => fileTree.setChildren(node.path, children))
+            .catch((err) =>
+              console.error(`Error loading children of ${node.path}:`, err)
+            )
+            .finally(() => loadingFolders.current.delete(node.path));
+        }
+      }
+    };
+    visit(fileTree.data);
+  }, [fileTree.data, expandedFolders, workspacePath]);
+
   const loadWorkspace = async (dirPath: string) => {
     try {
       await fileTree.loadFileTree(dirPath);
@@ -0,0 +1,5 @@
+import type { FileSelection } from "../../types";
+
+// Path of a selection, whether it's a plain path or carries ranges and a mode
+export const selectionPath = (selection: FileSelection): string =>
+  typeof selection === "string" ? selection : selection.path;
@@ -2,16 +2,28 @@ import { invoke } from "@tauri-apps/api/core";
 import { getVersion as tauriGetVersion } from "@tauri-apps/api/app";
 import {
   TreeOption,
+  ClipboardSummary,
+  ExportSummary,
+  ContentTransforms,
+  ExportFormat,
+  ExportOptions,
+  ExportParts,
+  FileSelection,
   FileTreeNode,
+  IgnoreFileError,
   RecentWorkspace,
   LocalLicenseState,
   ApplicationSettings,
+  PromptTemplate,
+  RedactionRule,
+  TransformSavings,
   WorkspaceLimitStatus,
 } from "../types";
 import { Store, load } from "@tauri-apps/plugin-store";
 import {
   type ConfirmDialogOptions,
   open,
+  save,
   confirm as tauriConfirm,
 } from "@tauri-apps/plugin-dialog";
 import {
@@ -78,6 +90,16 @@ export const calculateTokensForFiles = async (filePaths: string[]) => {
   return tokenMap;
 };
 
+export const calculateTransformSavings = async (
+  selections: FileSelection[],
+  transforms: ContentTransforms
+) => {
+  return invoke<TransformSavings>("calculate_transform_savings", {
+    selections,
+    transforms,
+  });
+};
+
 export const getFileTree = async (dirPath: string, withTokensSync = false) => {
   const tree = await invoke<FileTreeNode[]>("get_file_tree", {
     dirPath,
@@ -86,6 +108,23 @@ export const getFileTree = async (dirPath: string, withTokensSync = false) => {
   return tree;
 };
 
+// Children of a lazily loaded directory, `depth` levels deep or everything
+// below it for null
+export const getDirectoryChildren = async (
+  workspacePath: string,
+  path: string,
+  depth: number | null
+) =>
+  invoke<FileTreeNode[]>("get_directory_children", {
+    workspacePath,
+    path,
+    depth,
+  });
+
+// Ignore files in the workspace with lines that couldn't be parsed
+export const getIgnoreFileErrors = async (dirPath: string) =>
+  invoke<IgnoreFileError[]>("get_ignore_file_errors", { dirPath });
+
 export const openWorkspace = async (
   dirPath: string
 ): Promise<{
@@ -109,16 +148,72 @@ export const closeWorkspace = async () => {
 
 export const copyFilesWithTreeToClipboard = (
   dirPath: string,
-  selectedFilePaths: string[],
-  treeOption: TreeOption
+  selectedFilePaths: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
 ) => {
-  return invoke("copy_files_with_tree_to_clipboard", {
+  // Resolves to the files left out for the token budget and the secrets
+  // that were redacted from the export
+  return invoke<ClipboardSummary>("copy_files_with_tree_to_clipboard", {
+    dirPath,
+    selectedFilePaths,
+    treeOption,
+    options,
+  });
+};
+
+const EXPORT_EXTENSIONS: Record<ExportFormat, string> = {
+  classic: "txt",
+  xml: "xml",
+  markdown: "md",
+  json: "json",
+  "plain-text": "txt",
+};
+
+// Streams the export to a file picked in a save dialog; resolves to null when
+// the dialog is cancelled
+export const exportToFile = async (
+  dirPath: string,
+  selectedFilePaths: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
+): Promise<ExportSummary | null> => {
+  const extension = EXPORT_EXTENSIONS[options?.format ?? "classic"];
+  const outputPath = await save({
+    defaultPath: `export.${extension}`,
+    filters: [{ name: "Export", extensions: [extension] }],
+  });
+  if (!outputPath) return null;
+  return invoke<ExportSummary>("export_to_file", {
     dirPath,
     selectedFilePaths,
     treeOption,
+    outputPath,
+    options,
   });
 };
 
+// Without maxTokens the part limit from settings is used
+export const splitExportIntoParts = (
+  dirPath: string,
+  selectedFilePaths: FileSelection[],
+  treeOption: TreeOption,
+  maxTokens?: number,
+  options?: ExportOptions
+) => {
+  return invoke<ExportParts>("split_export_into_parts", {
+    dirPath,
+    selectedFilePaths,
+    treeOption,
+    maxTokens,
+    options,
+  });
+};
+
+export const copyTextToClipboard = (text: string) => {
+  return invoke<void>("copy_text_to_clipboard", { text });
+};
+
 let _recentWorkspacesStore: Store | null = null;
 
 // Helper function to lazily load the store
@@ -334,15 +429,52 @@ export const getApplicationSettings = async (): Promise<
   }
 };
 
+// Returns the open workspace's rebuilt tree when the ignore patterns changed
 export const updateApplicationSettings = async (
-  settings: ApplicationSettings
-): Promise<{ error: TauriApiError | null }> => {
+  settings: ApplicationSettings,
+  workspacePath: string | null = null
+): Promise<{ tree: FileTreeNode[] | null; error: TauriApiError | null }> => {
   try {
-    await invoke<void>("update_application_settings", { settings });
-    return { error: null };
+    const tree = await invoke<FileTreeNode[] | null>(
+      "update_application_settings",
+      { settings, workspacePath }
+    );
+    return { tree, error: null };
   } catch (error) {
     const { error: apiError } = createErrorResponse(error);
-    return { error: apiError };
+    return { tree: null, error: apiError };
+  }
+};
+
+export const getPromptTemplates = async (): Promise<PromptTemplate[]> => {
+  return invoke<PromptTemplate[]>("get_prompt_templates");
+};
+
+// Resolves with the validation message when a template is rejected
+export const updatePromptTemplates = async (
+  templates: PromptTemplate[]
+): Promise<{ error: string | null }> => {
+  try {
+    await invoke<void>("update_prompt_templates", { templates });
+    return { error: null };
+  } catch (error) {
+    return { error: String(error) };
+  }
+};
+
+// The ignore patterns new installs start with
+export const getDefaultIgnorePatterns = async (): Promise<string[]> =>
+  invoke<string[]>("get_default_ignore_patterns");
+
+// Resolves to the first rule whose pattern doesn't compile, if any
+export const validateRedactionRules = async (
+  rules: RedactionRule[]
+): Promise<{ error: string | null }> => {
+  try {
+    await invoke<void>("validate_redaction_rules", { rules });
+    return { error: null };
+  } catch (error) {
+    return { error: String(error) };
   }
 };
 
@@ -3,14 +3,25 @@
 
 import type {
   TreeOption,
+  ClipboardSummary,
+  ExportSummary,
+  ContentTransforms,
+  ExportOptions,
+  ExportParts,
+  FileSelection,
   FileTreeNode,
+  IgnoreFileError,
   RecentWorkspace,
   LocalLicenseState,
   ApplicationSettings,
+  PromptTemplate,
+  RedactionRule,
+  TransformSavings,
   WorkspaceLimitStatus,
 } from "../types";
 import { TauriApiError, RepoSizeCapError } from "./shared/errors";
 import { __WEB_DEMO__, __VSCODE__ } from "./shared/constants";
+import { selectionPath } from "./shared/file-selection";
 export { TauriApiError, __WEB_DEMO__, __VSCODE__, RepoSizeCapError };
 // @ts-ignore
 const vscode = acquireVsCodeApi();
@@ -86,6 +97,15 @@ export const calculateFileTokens = (filePath: string) =>
   rpc<number>("calculateFileTokens", { filePath });
 export const calculateTokensForFiles = (filePaths: string[]) =>
   rpc<Record<string, number>>("calculateTokensForFiles", { filePaths });
+// The extension host has no transform stage yet
+export const calculateTransformSavings = (
+  selections: FileSelection[],
+  transforms: ContentTransforms
+): Promise<TransformSavings | null> => {
+  void selections;
+  void transforms;
+  return Promise.resolve(null);
+};
 export const getFileTree = (dirPath: string, withTokensSync = false) =>
   rpc<FileTreeNode[]>("getFileTree", { dirPath, withTokensSync });
 export const openWorkspace = async (
@@ -114,15 +134,51 @@ export const openWorkspace = async (
 export const closeWorkspace = () => rpc<void>("closeWorkspace");
 export const copyFilesWithTreeToClipboard = (
   dirPath: string,
-  selectedFilePaths: string[],
-  treeOption: TreeOption
-): Promise<void> => // Ensure void return type to match others
-  rpc<void>("copyFilesWithTreeToClipboard", { // Expect void from rpc layer
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
+): Promise<ClipboardSummary | undefined> => // The extension host leaves nothing out
+  rpc<ClipboardSummary | undefined>("copyFilesWithTreeToClipboard", {
     dirPath,
-    selectedFilePaths,
+    // The extension host exports whole files only
+    selectedFilePaths: selections.map(selectionPath),
     treeOption,
+    options,
   });
 
+// The extension host only copies exports to the clipboard
+export const exportToFile = async (
+  dirPath: string,
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
+): Promise<ExportSummary | null> => {
+  void dirPath;
+  void selections;
+  void treeOption;
+  void options;
+  return Promise.resolve(null);
+};
+
+// The extension host doesn't split exports
+export const splitExportIntoParts = (
+  dirPath: string,
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  maxTokens?: number,
+  options?: ExportOptions
+): Promise<ExportParts> => {
+  void dirPath;
+  void selections;
+  void treeOption;
+  void maxTokens;
+  void options;
+  return Promise.resolve({ parts: [], redactions: [] });
+};
+
+export const copyTextToClipboard = (text: string): Promise<void> =>
+  navigator.clipboard.writeText(text);
+
 export const loadRecentWorkspaces = () =>
   rpc<RecentWorkspace[]>("loadRecentWorkspaces");
 export const saveRecentWorkspaces = (w: RecentWorkspace[]) =>
@@ -153,8 +209,57 @@ export const getApplicationSettings = async (): Promise<
   }
 };
 
-export const updateApplicationSettings = (s: ApplicationSettings) =>
-  rpc<void>("updateApplicationSettings", { s });
+// The extension host has no ignore pattern settings, so there's no tree to rebuild
+export const updateApplicationSettings = async (
+  s: ApplicationSettings,
+  workspacePath: string | null = null
+): Promise<{ tree: FileTreeNode[] | null }> => {
+  void workspacePath;
+  await rpc<void>("updateApplicationSettings", { s });
+  return { tree: null };
+};
+
+// The extension host doesn't support prompt templates yet
+export const getPromptTemplates = (): Promise<PromptTemplate[]> =>
+  Promise.resolve([]);
+export const updatePromptTemplates = (
+  templates: PromptTemplate[]
+): Promise<{ error: string | null }> => {
+  void templates;
+  return Promise.resolve({ error: null });
+};
+
+// Redaction rules are only applied by the desktop exporter
+export const validateRedactionRules = async (
+  rules: RedactionRule[]
+): Promise<{ error: string | null }> => {
+  void rules;
+  return Promise.resolve({ error: null });
+};
+
+// The extension always loads the whole tree
+export const getDirectoryChildren = async (
+  workspacePath: string,
+  path: string,
+  depth: number | null
+): Promise<FileTreeNode[]> => {
+  void workspacePath;
+  void path;
+  void depth;
+  return Promise.resolve([]);
+};
+
+// .treesnapignore files are only read by the desktop app
+export const getIgnoreFileErrors = async (
+  dirPath: string
+): Promise<IgnoreFileError[]> => {
+  void dirPath;
+  return Promise.resolve([]);
+};
+
+// Ignore patterns are only edited in the desktop app
+export const getDefaultIgnorePatterns = async (): Promise<string[]> =>
+  Promise.resolve([]);
 
 /* debug helpers are forwarded unchanged */
 export const debugSetLicenseState = (p: any) =>
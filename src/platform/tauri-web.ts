@@ -1,9 +1,19 @@
 import {
   TreeOption,
+  ClipboardSummary,
+  ExportSummary,
+  ContentTransforms,
+  ExportOptions,
+  ExportParts,
+  FileSelection,
   FileTreeNode,
+  IgnoreFileError,
   RecentWorkspace,
   LocalLicenseState,
   ApplicationSettings,
+  PromptTemplate,
+  RedactionRule,
+  TransformSavings,
   WorkspaceLimitStatus,
 } from "../types"; // Assuming types are here
 import { type Event, type UnlistenFn } from "@tauri-apps/api/event"; // Keep type imports if needed
@@ -17,6 +27,7 @@ import {
 import { renderAsciiTree } from "./shared/render-ascii-tree";
 import { buildDemoExportText } from "./demo/demo-ascii-tree";
 import { detectBinary } from "./shared/bin-utils";
+import { selectionPath } from "./shared/file-selection";
 import {
   TauriApiError,
   LicenseStateResponse,
@@ -139,6 +150,16 @@ export const calculateTokensForFiles = async (
   return Promise.resolve(tokenMap);
 };
 
+// The demo export never applies content transforms
+export const calculateTransformSavings = async (
+  selections: FileSelection[],
+  transforms: ContentTransforms
+): Promise<TransformSavings | null> => {
+  void selections;
+  void transforms;
+  return Promise.resolve(null);
+};
+
 // Return demo tree, VFS tree, or empty
 export const getFileTree = async (
   dirPath: string,
@@ -212,9 +233,13 @@ export const closeWorkspace = async (workspacePath: string): Promise<void> => {
 // Cannot access local filesystem to copy
 export const copyFilesWithTreeToClipboard = async (
   dirPath: string,
-  selectedFilePaths: string[],
-  treeOption: TreeOption
-): Promise<void> => {
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
+): Promise<ClipboardSummary> => {
+  void options; // The demo export always uses the classic layout
+  // Ranges and outlines need the desktop exporter, the demo exports whole files
+  const selectedFilePaths = selections.map(selectionPath);
 
   let exportText = "";
 
@@ -227,7 +252,7 @@ export const copyFilesWithTreeToClipboard = async (
         `WEB SHIM: Cannot copy, VFS workspace '${dirPath}' not found.`
       );
       alert("Error: Could not find workspace data to copy.");
-      return;
+      return { omittedFiles: [], redactions: [] };
     }
     /* ---------- reuse demo helpers so format is identical ---------- */
     const rootLabel =
@@ -273,7 +298,7 @@ export const copyFilesWithTreeToClipboard = async (
   } else {
     console.error(`WEB SHIM: Cannot copy, unsupported path type '${dirPath}'.`);
     alert("Error: Cannot copy data for this workspace type.");
-    return;
+    return { omittedFiles: [], redactions: [] };
   }
 
   // Running standalone (new tab): Use navigator.clipboard directly
@@ -285,6 +310,42 @@ export const copyFilesWithTreeToClipboard = async (
     // Provide feedback in the demo UI
     alert("Copy is not supported in this browser.");
   }
+  // The demo has no token budget and its workspaces hold no secrets
+  return { omittedFiles: [], redactions: [] };
+};
+
+// The web demo only copies exports to the clipboard
+export const exportToFile = async (
+  dirPath: string,
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  options?: ExportOptions
+): Promise<ExportSummary | null> => {
+  void dirPath;
+  void selections;
+  void treeOption;
+  void options;
+  return Promise.resolve(null);
+};
+
+// Splitting needs the desktop exporter
+export const splitExportIntoParts = async (
+  dirPath: string,
+  selections: FileSelection[],
+  treeOption: TreeOption,
+  maxTokens?: number,
+  options?: ExportOptions
+): Promise<ExportParts> => {
+  void dirPath;
+  void selections;
+  void treeOption;
+  void maxTokens;
+  void options;
+  return Promise.resolve({ parts: [], redactions: [] });
+};
+
+export const copyTextToClipboard = async (text: string): Promise<void> => {
+  await navigator.clipboard.writeText(text);
 };
 
 // --- Recent Workspaces (Now includes Demo) ---
@@ -430,20 +491,92 @@ export const getApplicationSettings = async (): Promise<{
   console.log("WEB SHIM: getApplicationSettings called.");
   // Matches the Rust Default implementation
   const defaultSettings: ApplicationSettings = {
-    schemaVersion: 1,
+    schemaVersion: 2,
     appVersion: "web-demo",
     treeOption: "include",
+    // The web demo has its own ignore list
+    ignorePatterns: [],
+    treeLoadDepth: null,
+    treeStyle: "ascii",
+    exportFormat: "classic",
+    relativePaths: true,
+    fileMap: { annotate: false, collapseChains: false },
+    transforms: {
+      stripComments: false,
+      collapseBlankLines: false,
+      trimTrailingWhitespace: false,
+    },
+    lineNumbers: false,
+    diff: { against: "head", contextLines: 3 },
+    redaction: { enabled: true, customRules: [] },
+    tokenBudget: null,
+    partTokenLimit: 32000,
+    languageOverrides: {},
+    fileSizeCap: {
+      enabled: false,
+      maxBytes: 1048576,
+      maxTokens: 50000,
+      headLines: 200,
+      tailLines: 50,
+    },
+    metadataHeaderFormats: [],
   };
   return Promise.resolve({ settings: defaultSettings, error: null });
 };
 
+// Prompt templates need the desktop exporter
+export const getPromptTemplates = async (): Promise<PromptTemplate[]> => {
+  return Promise.resolve([]);
+};
+
+export const updatePromptTemplates = async (
+  templates: PromptTemplate[]
+): Promise<{ error: string | null }> => {
+  void templates;
+  return Promise.resolve({ error: null });
+};
+
+// Redaction rules are only applied by the desktop exporter
+export const validateRedactionRules = async (
+  rules: RedactionRule[]
+): Promise<{ error: string | null }> => {
+  void rules;
+  return Promise.resolve({ error: null });
+};
+
+// The web demo always loads the whole tree
+export const getDirectoryChildren = async (
+  workspacePath: string,
+  path: string,
+  depth: number | null
+): Promise<FileTreeNode[]> => {
+  void workspacePath;
+  void path;
+  void depth;
+  return Promise.resolve([]);
+};
+
+// .treesnapignore files are only read by the desktop app
+export const getIgnoreFileErrors = async (
+  dirPath: string
+): Promise<IgnoreFileError[]> => {
+  void dirPath;
+  return Promise.resolve([]);
+};
+
+// Ignore patterns are only edited in the desktop app
+export const getDefaultIgnorePatterns = async (): Promise<string[]> =>
+  Promise.resolve([]);
+
 // Settings update is a no-op in the web demo
 export const updateApplicationSettings = async (
-  settings: ApplicationSettings
-): Promise<{ error: TauriApiError | null }> => {
+  settings: ApplicationSettings,
+  workspacePath: string | null = null
+): Promise<{ tree: FileTreeNode[] | null; error: TauriApiError | null }> => {
   void settings; // Mark as unused
+  void workspacePath;
   console.log("WEB SHIM: updateApplicationSettings called (no-op).");
-  return Promise.resolve({ error: null });
+  return Promise.resolve({ tree: null, error: null });
 };
 
 // --- End Settings Service Stubs ---
@@ -6,13 +6,24 @@ export interface FileTreeNode {
   path: string;
   children?: FileTreeNode[];
   is_directory: boolean;
+  // Whether a directory has anything in it; a lazily loaded one without
+  // children hasn't been expanded yet
+  has_children?: boolean;
   selected?: boolean;
   tokenCount?: number;
   token_count?: number;
+  // File size in bytes; null for directories
+  size?: number | null;
+  // Code fence language, e.g. "rust" or "dockerfile"; null when unknown
+  language?: string | null;
+  // Encoding of a text file, e.g. "UTF-8" or "Shift_JIS"; exports are always UTF-8
+  encoding?: string | null;
   parent?: string;
   dirPercentage?: number;
   isLoading?: boolean;
   selectionState?: "none" | "partial" | "all";
+  // client side only, defaults to "full"
+  contentMode?: ContentMode;
 }
 
 export interface FileChangeEvent {
@@ -20,11 +31,149 @@ export interface FileChangeEvent {
   kind: "create" | "modify" | "remove";
 }
 
+// An ignore file with lines that couldn't be parsed; its other lines still apply
+export interface IgnoreFileError {
+  path: string;
+  message: string;
+}
+
 export interface RecentWorkspace {
   path: string;
 }
 
-export type TreeOption = "include" | "include-only-selected" | "do-not-include";
+export type TreeOption =
+  | "include"
+  | "include-only-selected"
+  // Paths to the selected files, with counts of everything next to them
+  | "include-selected-paths"
+  | "do-not-include";
+
+// How the file map lists the files
+export type TreeStyle = "ascii" | "path-list" | "markdown-list" | "json";
+
+export type ExportFormat = "classic" | "xml" | "markdown" | "json" | "plain-text";
+
+export type BudgetPriority =
+  | "smallest-first"
+  | "selection-order"
+  | "most-recently-modified";
+
+export interface TokenBudget {
+  maxTokens: number;
+  priority?: BudgetPriority;
+  truncateOverflow?: boolean;
+}
+
+// Inclusive, 1-based
+export interface LineRange {
+  start: number;
+  end: number;
+}
+
+// "outline" keeps signatures and doc comments but drops function bodies
+export type ContentMode = "full" | "outline";
+
+// A plain path exports the whole file
+export type FileSelection =
+  | string
+  | { path: string; ranges?: LineRange[]; mode?: ContentMode };
+
+// Clean-ups applied to file content before it is exported
+export interface ContentTransforms {
+  stripComments: boolean;
+  collapseBlankLines: boolean;
+  trimTrailingWhitespace: boolean;
+}
+
+export interface TransformSavings {
+  tokensBefore: number;
+  tokensAfter: number;
+}
+
+// What a diff export compares the working tree against
+export type DiffOptions = (
+  | { against: "head" }
+  | { against: "ref"; ref: string }
+  | { against: "staged" }
+) & {
+  // Unchanged lines shown around each hunk, 3 by default
+  contextLines?: number;
+};
+
+// A named prompt wrapping the export. The body may use {{file_map}},
+// {{file_contents}}, {{instructions}}, {{token_count}} and {{workspace_name}}
+export interface PromptTemplate {
+  name: string;
+  body: string;
+}
+
+// A user-defined pattern whose matches are replaced with [REDACTED:<name>]. With a
+// capture group only the group is replaced
+export interface RedactionRule {
+  name: string;
+  pattern: string;
+}
+
+export interface RedactionSettings {
+  enabled: boolean;
+  customRules: RedactionRule[];
+}
+
+// A value replaced in an export, at its 1-based line in the file on disk
+export interface RedactedSecret {
+  path: string;
+  kind: string;
+  line: number;
+}
+
+// One part of an export split to fit a token limit; content starts with a
+// header explaining the sequence
+export interface ExportPart {
+  number: number;
+  total: number;
+  content: string;
+  tokenCount: number;
+}
+
+// A selected file left out because it didn't fit the token budget
+export interface OmittedFile {
+  path: string;
+  tokenCount: number;
+}
+
+// What was kept out of an export copied to the clipboard
+export interface ClipboardSummary {
+  omittedFiles: OmittedFile[];
+  redactions: RedactedSecret[];
+}
+
+// What an export written to a file contained
+export interface ExportSummary {
+  path: string;
+  fileCount: number;
+  bytesWritten: number;
+  tokenCount: number;
+  omittedFiles: OmittedFile[];
+  redactions: RedactedSecret[];
+}
+
+export interface ExportParts {
+  parts: ExportPart[];
+  redactions: RedactedSecret[];
+}
+
+// Per-export choices; anything left out falls back to the saved settings
+export interface ExportOptions {
+  format?: ExportFormat;
+  tokenBudget?: TokenBudget;
+  transforms?: ContentTransforms;
+  lineNumbers?: boolean;
+  // Export each file's diff instead of its content
+  diff?: DiffOptions;
+  // Name of the prompt template to wrap the export in
+  template?: string;
+  instructions?: string;
+}
 
 export interface LocalLicenseState {
   status: "inactive" | "activated" | "expired";
@@ -39,10 +188,57 @@ export interface ApiError {
   message: string;
 }
 
+// Limits on how much of any one file goes into an export; files over them
+// keep only their first and last lines
+export interface FileSizeCap {
+  enabled: boolean;
+  maxBytes: number;
+  maxTokens: number;
+  headLines: number;
+  tailLines: number;
+}
+
+// How the file map at the top of an export is drawn
+export interface FileMapOptions {
+  // Token counts and sizes next to files, token totals next to directories
+  annotate: boolean;
+  // Deeper directories show only their file count; null for no limit
+  maxDepth?: number | null;
+  // Directories holding a single directory show as one "a/b/c/" entry
+  collapseChains: boolean;
+  // Unselected directories with more files show as "dir/ (143 files)"
+  elideOver?: number | null;
+}
+
 export interface ApplicationSettings {
   schemaVersion: number;
   appVersion: string;
   treeOption: TreeOption;
+  ignorePatterns: string[];
+  // Tree levels loaded when a workspace opens; null loads everything
+  treeLoadDepth: number | null;
+  treeStyle: TreeStyle;
+  exportFormat: ExportFormat;
+  relativePaths: boolean;
+  rootDisplayName?: string | null;
+  fileMap: FileMapOptions;
+  transforms: ContentTransforms;
+  // Prefix every exported line with its number in the file
+  lineNumbers: boolean;
+  diff: DiffOptions;
+  promptTemplate?: string | null;
+  redaction: RedactionSettings;
+  // Budget every export is fitted into; null exports every selected file
+  tokenBudget: TokenBudget | null;
+  // Most tokens in each part of a split export
+  partTokenLimit: number;
+  // Code fence languages that replace the detected ones, keyed by exact file
+  // name ("Jenkinsfile") or extension pattern ("*.h")
+  languageOverrides: Record<string, string>;
+  fileSizeCap: FileSizeCap;
+  // Formats whose exports start with a header recording the workspace, git
+  // revision, file and token counts
+  metadataHeaderFormats: ExportFormat[];
 }
 
 export interface WorkspaceLimitStatus {
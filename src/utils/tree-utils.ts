@@ -16,6 +16,29 @@ export const findNodeByPath = (
   return null;
 };
 
+// A copy of the tree with the children of the directory at `path` filled in
+export const withChildren = (
+  nodes: FileTreeNode[],
+  path: string,
+  children: FileTreeNode[]
+): FileTreeNode[] =>
+  nodes.map((node) => {
+    if (node.path === path) {
+      return { ...node, children };
+    }
+    if (node.children && path.startsWith(node.path)) {
+      return { ...node, children: withChildren(node.children, path, children) };
+    }
+    return node;
+  });
+
+// Whether a lazily loaded directory still has unexpanded directories below it
+export const hasUnloadedDirectories = (node: FileTreeNode): boolean =>
+  node.is_directory &&
+  (node.children
+    ? node.children.some(hasUnloadedDirectories)
+    : !!node.has_children);
+
 export const getAllDescendants = (node: FileTreeNode): FileTreeNode[] => {
   let items: FileTreeNode[] = [node];
   if (node.children) {